@@ -11,6 +11,8 @@ pub type PortLocations = std::collections::HashMap<AnyParameterId, Pos2>;
 pub type NodeRects = std::collections::HashMap<NodeId, Rect>;
 
 const DISTANCE_TO_CONNECT: f32 = 10.0;
+/// Opacity multiplier for nodes and connections drawn faded out.
+const FADED_OPACITY: f32 = 0.3;
 
 /// Nodes communicate certain events to the parent graph when drawn. There is
 /// one special `User` variant which can be used by users as the return value
@@ -282,13 +284,15 @@ where
         }
 
         /* Draw connections */
-        if let Some((_, ref locator)) = self.connection_in_progress {
+        if let Some((origin_node, ref locator)) = self.connection_in_progress {
             let port_type = self.graph.any_param_type(*locator).unwrap();
             let connection_color = port_type.data_type_color(user_state);
             let start_pos = port_locations[locator];
 
-            // Find a port to connect to
-            fn snap_to_ports<
+            // Find the port to connect to. When several compatible ports are
+            // in range, the one closest to the cursor wins, so dense nodes
+            // don't get ambiguous connections.
+            fn closest_port<
                 NodeData,
                 UserState,
                 DataType: DataTypeTrait<UserState>,
@@ -297,56 +301,69 @@ where
                 Value,
             >(
                 graph: &Graph<NodeData, DataType, ValueType>,
+                origin_node: NodeId,
                 port_type: &DataType,
                 ports: &SlotMap<Key, Value>,
                 port_locations: &PortLocations,
                 cursor_pos: Pos2,
-            ) -> Pos2 {
+                max_distance: f32,
+            ) -> Option<(AnyParameterId, Pos2)> {
                 ports
                     .iter()
-                    .find_map(|(port_id, _)| {
+                    .filter_map(|(port_id, _)| {
+                        let port_id: AnyParameterId = port_id.into();
                         let compatible_ports = graph
-                            .any_param_type(port_id.into())
-                            .map(|other| other == port_type)
-                            .unwrap_or(false);
-
-                        if compatible_ports {
-                            port_locations.get(&port_id.into()).and_then(|port_pos| {
-                                if port_pos.distance(cursor_pos) < DISTANCE_TO_CONNECT {
-                                    Some(*port_pos)
-                                } else {
-                                    None
-                                }
+                            .any_param_type(port_id)
+                            .map(|other| match port_id {
+                                AnyParameterId::Input(_) => port_type.can_connect_to(other),
+                                AnyParameterId::Output(_) => other.can_connect_to(port_type),
                             })
+                            .unwrap_or(false);
+                        // Don't allow self-loops
+                        let port_node = match port_id {
+                            AnyParameterId::Input(input) => graph[input].node,
+                            AnyParameterId::Output(output) => graph[output].node,
+                        };
+
+                        if compatible_ports && port_node != origin_node {
+                            let port_pos = *port_locations.get(&port_id)?;
+                            let distance = port_pos.distance(cursor_pos);
+                            (distance < max_distance).then_some((port_id, port_pos, distance))
                         } else {
                             None
                         }
                     })
-                    .unwrap_or(cursor_pos)
+                    .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b))
+                    .map(|(port_id, port_pos, _)| (port_id, port_pos))
             }
 
-            let (src_pos, dst_pos) = match locator {
-                AnyParameterId::Output(_) => (
-                    start_pos,
-                    snap_to_ports(
-                        &self.graph,
-                        port_type,
-                        &self.graph.inputs,
-                        &port_locations,
-                        cursor_pos,
-                    ),
+            let max_distance = DISTANCE_TO_CONNECT * self.pan_zoom.zoom;
+            let target = match locator {
+                AnyParameterId::Output(_) => closest_port(
+                    &self.graph,
+                    origin_node,
+                    port_type,
+                    &self.graph.inputs,
+                    &port_locations,
+                    cursor_pos,
+                    max_distance,
                 ),
-                AnyParameterId::Input(_) => (
-                    snap_to_ports(
-                        &self.graph,
-                        port_type,
-                        &self.graph.outputs,
-                        &port_locations,
-                        cursor_pos,
-                    ),
-                    start_pos,
+                AnyParameterId::Input(_) => closest_port(
+                    &self.graph,
+                    origin_node,
+                    port_type,
+                    &self.graph.outputs,
+                    &port_locations,
+                    cursor_pos,
+                    max_distance,
                 ),
             };
+
+            let snapped_pos = target.map(|(_, pos)| pos).unwrap_or(cursor_pos);
+            let (src_pos, dst_pos) = match locator {
+                AnyParameterId::Output(_) => (start_pos, snapped_pos),
+                AnyParameterId::Input(_) => (snapped_pos, start_pos),
+            };
             draw_connection(
                 &self.pan_zoom,
                 ui.painter(),
@@ -354,6 +371,29 @@ where
                 dst_pos,
                 connection_color,
             );
+
+            // Hint the port the connection would be made to.
+            if let Some((_, target_pos)) = target {
+                ui.painter().circle(
+                    target_pos,
+                    5.0 * self.pan_zoom.zoom,
+                    color_from_hex("#4caf50").unwrap(),
+                    Stroke::NONE,
+                );
+            }
+
+            if let Some((target_port, _)) = target {
+                if ui.input(|i| i.pointer.any_released()) {
+                    match (*locator, target_port) {
+                        (AnyParameterId::Input(input), AnyParameterId::Output(output))
+                        | (AnyParameterId::Output(output), AnyParameterId::Input(input)) => {
+                            delayed_responses
+                                .push(NodeResponse::ConnectEventEnded { input, output });
+                        }
+                        _ => { /* Ignore in-in or out-out connections */ }
+                    }
+                }
+            }
         }
 
         for (input, output) in self.graph.iter_connections() {
@@ -361,7 +401,17 @@ where
                 .graph
                 .any_param_type(AnyParameterId::Output(output))
                 .unwrap();
-            let connection_color = port_type.data_type_color(user_state);
+            let faded = [self.graph[output].node, self.graph[input].node]
+                .iter()
+                .any(|node| {
+                    self.graph[*node]
+                        .user_data
+                        .faded(*node, &self.graph, user_state)
+                });
+            let mut connection_color = port_type.data_type_color(user_state);
+            if faded {
+                connection_color = connection_color.gamma_multiply(FADED_OPACITY);
+            }
             let src_pos = port_locations[&AnyParameterId::Output(output)];
             let dst_pos = port_locations[&AnyParameterId::Input(input)];
             draw_connection(
@@ -497,10 +547,13 @@ where
             self.pan_zoom.pan += ui.ctx().input(|i| i.pointer.delta());
         }
 
-        // Deselect and deactivate finder if the editor backround is clicked,
-        // *or* if the the mouse clicks off the ui
-        if click_on_background || (mouse.any_click() && !cursor_in_editor) {
+        // Deselect and deactivate finder if the editor backround is clicked.
+        // Clicking off the ui only closes the finder: The selection is kept so
+        // user code can act on it from widgets outside the editor.
+        if click_on_background {
             self.selected_nodes = Vec::new();
+        }
+        if click_on_background || (mouse.any_click() && !cursor_in_editor) {
             self.node_finder = None;
         }
 
@@ -590,6 +643,11 @@ where
         let margin = egui::vec2(15.0, 5.0) * pan_zoom.zoom;
         let mut responses = Vec::<NodeResponse<UserResponse, NodeData>>::new();
 
+        let faded = self.graph[self.node_id]
+            .user_data
+            .faded(self.node_id, self.graph, user_state);
+        let opacity = if faded { FADED_OPACITY } else { 1.0 };
+
         let background_color;
         let text_color;
         if ui.visuals().dark_mode {
@@ -599,9 +657,13 @@ where
             background_color = color_from_hex("#ffffff").unwrap();
             text_color = color_from_hex("#505050").unwrap();
         }
+        let text_color = text_color.gamma_multiply(opacity);
 
         ui.visuals_mut().widgets.noninteractive.fg_stroke =
             Stroke::new(2.0 * pan_zoom.zoom, text_color);
+        if faded {
+            ui.visuals_mut().override_text_color = Some(text_color);
+        }
 
         // Preallocate shapes to paint below contents
         let outline_shape = ui.painter().add(Shape::Noop);
@@ -767,6 +829,7 @@ where
             port_locations: &mut PortLocations,
             ongoing_drag: Option<(NodeId, AnyParameterId)>,
             is_connected_input: bool,
+            opacity: f32,
         ) where
             DataType: DataTypeTrait<UserState>,
             UserResponse: UserResponseTrait,
@@ -793,9 +856,36 @@ where
             };
 
             let port_color = if close_enough {
-                Color32::WHITE
+                match ongoing_drag {
+                    // While dragging a connection, hint that releasing it over
+                    // this port wouldn't create a connection. The port that
+                    // would get connected is hinted once the closest one is
+                    // known, when drawing the ongoing connection.
+                    Some((origin_node, origin_param)) if origin_param != param_id => {
+                        let origin_type = graph.any_param_type(origin_param).unwrap();
+                        let compatible = match (origin_param, param_id) {
+                            (AnyParameterId::Output(_), AnyParameterId::Input(_)) => {
+                                origin_type.can_connect_to(port_type)
+                            }
+                            (AnyParameterId::Input(_), AnyParameterId::Output(_)) => {
+                                port_type.can_connect_to(origin_type)
+                            }
+                            _ => false,
+                        };
+                        if origin_node != node_id && compatible {
+                            port_type
+                                .data_type_color(user_state)
+                                .gamma_multiply(opacity)
+                        } else {
+                            color_from_hex("#e53935").unwrap()
+                        }
+                    }
+                    _ => Color32::WHITE,
+                }
             } else {
-                port_type.data_type_color(user_state)
+                port_type
+                    .data_type_color(user_state)
+                    .gamma_multiply(opacity)
             };
             ui.painter().circle(
                 port_rect.center(),
@@ -819,24 +909,6 @@ where
                 }
             }
 
-            if let Some((origin_node, origin_param)) = ongoing_drag {
-                if origin_node != node_id {
-                    // Don't allow self-loops
-                    if graph.any_param_type(origin_param).unwrap() == port_type
-                        && close_enough
-                        && ui.input(|i| i.pointer.any_released())
-                    {
-                        match (param_id, origin_param) {
-                            (AnyParameterId::Input(input), AnyParameterId::Output(output))
-                            | (AnyParameterId::Output(output), AnyParameterId::Input(input)) => {
-                                responses.push(NodeResponse::ConnectEventEnded { input, output });
-                            }
-                            _ => { /* Ignore in-in or out-out connections */ }
-                        }
-                    }
-                }
-            }
-
             port_locations.insert(param_id, port_rect.center());
         }
 
@@ -866,6 +938,7 @@ where
                     self.port_locations,
                     self.ongoing_drag,
                     self.graph.connection(*param).is_some(),
+                    opacity,
                 );
             }
         }
@@ -889,6 +962,7 @@ where
                 self.port_locations,
                 self.ongoing_drag,
                 false,
+                opacity,
             );
         }
 
@@ -909,7 +983,8 @@ where
                 fill: self.graph[self.node_id]
                     .user_data
                     .titlebar_color(ui, self.node_id, self.graph, user_state)
-                    .unwrap_or_else(|| background_color.lighten(0.8)),
+                    .unwrap_or_else(|| background_color.lighten(0.8))
+                    .gamma_multiply(opacity),
                 stroke: Stroke::NONE,
                 fill_texture_id: Default::default(),
                 uv: Rect::ZERO,
@@ -922,7 +997,7 @@ where
             let body = Shape::Rect(RectShape {
                 rect: body_rect,
                 rounding: Rounding::ZERO,
-                fill: background_color,
+                fill: background_color.gamma_multiply(opacity),
                 stroke: Stroke::NONE,
                 fill_texture_id: Default::default(),
                 uv: Rect::ZERO,
@@ -935,7 +1010,7 @@ where
             let bottom_body = Shape::Rect(RectShape {
                 rect: bottom_body_rect,
                 rounding,
-                fill: background_color,
+                fill: background_color.gamma_multiply(opacity),
                 stroke: Stroke::NONE,
                 fill_texture_id: Default::default(),
                 uv: Rect::ZERO,
@@ -85,6 +85,16 @@ pub trait DataTypeTrait<UserState>: PartialEq + Eq {
     /// }
     /// ```
     fn name(&self) -> std::borrow::Cow<str>;
+
+    /// Whether an output of this datatype can be connected to an input of the
+    /// `input` datatype. By default, only matching datatypes can be connected.
+    ///
+    /// Override this to allow implicit conversions between datatypes. Note that
+    /// the library doesn't convert anything by itself, it is up to the user code
+    /// to convert the values when evaluating the graph.
+    fn can_connect_to(&self, input: &Self) -> bool {
+        self == input
+    }
 }
 
 /// This trait must be implemented for the `NodeData` generic parameter of the
@@ -158,6 +168,20 @@ where
         None
     }
 
+    /// Whether to draw the node faded out, e.g. to draw attention to other
+    /// parts of the graph. Connections to and from faded nodes are faded out
+    /// as well.
+    ///
+    /// Default implementation returns false.
+    fn faded(
+        &self,
+        _node_id: NodeId,
+        _graph: &Graph<Self, Self::DataType, Self::ValueType>,
+        _user_state: &mut Self::UserState,
+    ) -> bool {
+        false
+    }
+
     /// Separator to put between elements in the node.
     ///
     /// Invoked between inputs, outputs and bottom UI. Useful for
@@ -1,8 +1,13 @@
-use std::{borrow::Cow, collections::HashMap};
+use std::{
+    borrow::Cow,
+    collections::{hash_map::Entry, HashMap, HashSet},
+};
 
 use eframe::egui::{self, DragValue, TextStyle};
 use egui_node_graph::*;
 
+use crate::curve::{curve_editor, Curve};
+
 // ========= First, define your user data types =============
 
 /// The NodeData holds a custom data struct inside each node. It's useful to
@@ -21,6 +26,9 @@ pub struct MyNodeData {
 pub enum MyDataType {
     Scalar,
     Vec2,
+    Vec4,
+    Color,
+    Bool,
 }
 
 /// In the graph, input parameters can optionally have a constant value. This
@@ -30,11 +38,14 @@ pub enum MyDataType {
 /// this library makes no attempt to check this consistency. For instance, it is
 /// up to the user code in this example to make sure no parameter is created
 /// with a DataType of Scalar and a ValueType of Vec2.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
 pub enum MyValueType {
     Vec2 { value: egui::Vec2 },
     Scalar { value: f32 },
+    Vec4 { value: [f32; 4] },
+    Color { value: [f32; 3] },
+    Bool { value: bool },
 }
 
 impl Default for MyValueType {
@@ -55,6 +66,121 @@ impl MyValueType {
         }
     }
 
+    /// Tries to downcast this value type to a color
+    pub fn try_to_color(self) -> anyhow::Result<[f32; 3]> {
+        if let MyValueType::Color { value } = self {
+            Ok(value)
+        } else {
+            anyhow::bail!("Invalid cast from {:?} to color", self)
+        }
+    }
+
+    /// Tries to downcast this value type to a 4d vector
+    pub fn try_to_vec4(self) -> anyhow::Result<[f32; 4]> {
+        if let MyValueType::Vec4 { value } = self {
+            Ok(value)
+        } else {
+            anyhow::bail!("Invalid cast from {:?} to vec4", self)
+        }
+    }
+
+    /// Tries to downcast this value type to a boolean
+    pub fn try_to_bool(self) -> anyhow::Result<bool> {
+        if let MyValueType::Bool { value } = self {
+            Ok(value)
+        } else {
+            anyhow::bail!("Invalid cast from {:?} to bool", self)
+        }
+    }
+
+    /// Converts this value to the given data type. Scalars are broadcast to
+    /// all components of the target type. Other values keep as many of their
+    /// components as fit in the target type, and missing components are
+    /// filled with zero, or one for alpha. For instance, a 2d vector becomes
+    /// (x, y, 0, 1) as a 4d vector. Booleans count as 0 or 1, and any nonzero
+    /// number is true.
+    pub fn convert_to(self, typ: &MyDataType) -> Self {
+        let components = self.components();
+        let component = |index: usize, default: f32| match components.as_slice() {
+            [value] => *value,
+            _ => components.get(index).copied().unwrap_or(default),
+        };
+        match typ {
+            MyDataType::Scalar => MyValueType::Scalar {
+                value: component(0, 0.0),
+            },
+            MyDataType::Vec2 => MyValueType::Vec2 {
+                value: egui::vec2(component(0, 0.0), component(1, 0.0)),
+            },
+            MyDataType::Vec4 => MyValueType::Vec4 {
+                value: [
+                    component(0, 0.0),
+                    component(1, 0.0),
+                    component(2, 0.0),
+                    component(3, 1.0),
+                ],
+            },
+            MyDataType::Color => MyValueType::Color {
+                value: [component(0, 0.0), component(1, 0.0), component(2, 0.0)],
+            },
+            MyDataType::Bool => MyValueType::Bool {
+                value: component(0, 0.0) != 0.0,
+            },
+        }
+    }
+
+    /// Returns the components of this value.
+    pub fn components(self) -> Vec<f32> {
+        match self {
+            MyValueType::Scalar { value } => vec![value],
+            MyValueType::Vec2 { value } => vec![value.x, value.y],
+            MyValueType::Vec4 { value } => value.to_vec(),
+            MyValueType::Color { value } => value.to_vec(),
+            MyValueType::Bool { value } => vec![if value { 1.0 } else { 0.0 }],
+        }
+    }
+
+    /// Applies `f` to each of the components of this value.
+    pub fn map_components(self, f: impl Fn(f32) -> f32) -> Self {
+        match self {
+            MyValueType::Scalar { value } => MyValueType::Scalar { value: f(value) },
+            MyValueType::Vec2 { value } => MyValueType::Vec2 {
+                value: egui::vec2(f(value.x), f(value.y)),
+            },
+            MyValueType::Vec4 { value } => MyValueType::Vec4 {
+                value: value.map(f),
+            },
+            MyValueType::Color { value } => MyValueType::Color {
+                value: value.map(f),
+            },
+            MyValueType::Bool { value } => MyValueType::Bool {
+                value: f(if value { 1.0 } else { 0.0 }) != 0.0,
+            },
+        }
+    }
+
+    /// Returns a value of the same type as this one, made of the given
+    /// components. See `components` for their order.
+    pub fn with_components(self, components: &[f32]) -> Self {
+        match self {
+            MyValueType::Scalar { .. } => MyValueType::Scalar {
+                value: components[0],
+            },
+            MyValueType::Vec2 { .. } => MyValueType::Vec2 {
+                value: egui::vec2(components[0], components[1]),
+            },
+            MyValueType::Vec4 { .. } => MyValueType::Vec4 {
+                value: [components[0], components[1], components[2], components[3]],
+            },
+            MyValueType::Color { .. } => MyValueType::Color {
+                value: [components[0], components[1], components[2]],
+            },
+            MyValueType::Bool { .. } => MyValueType::Bool {
+                value: components[0] != 0.0,
+            },
+        }
+    }
+
     /// Tries to downcast this value type to a scalar
     pub fn try_to_scalar(self) -> anyhow::Result<f32> {
         if let MyValueType::Scalar { value } = self {
@@ -68,7 +194,7 @@ impl MyValueType {
 /// NodeTemplate is a mechanism to define node templates. It's what the graph
 /// will display in the "new node" popup. The user code needs to tell the
 /// library how to convert a NodeTemplate into a Node.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
 pub enum MyNodeTemplate {
     MakeScalar,
@@ -78,6 +204,69 @@ pub enum MyNodeTemplate {
     AddVector,
     SubtractVector,
     VectorTimesScalar,
+    CurveRemap,
+    PolarCoordinates,
+    GammaCorrect,
+    TimeWrap,
+    SoftClamp,
+    Caustics,
+    Clamp,
+    ClampVector,
+    Abs,
+    AbsVector,
+    Floor,
+    Ceil,
+    Round,
+    FloorVector,
+    CeilVector,
+    RoundVector,
+    Frac,
+    FracVector,
+    WrapUv,
+    Mod,
+    Exp,
+    Log,
+    Log2,
+    MathConstant,
+    Pulse,
+    Tan,
+    Asin,
+    Acos,
+    Atan,
+    Atan2,
+    Length,
+    Distance,
+    Rsqrt,
+    OneMinus,
+    OneMinusVector,
+    Remap,
+    ThreeStopGradient,
+    Posterize,
+    PosterizeVector,
+    FlowDistort,
+    Panner,
+    UvDebug,
+    MakeVec4,
+    CombineAlpha,
+    MakeColor,
+    SignedPow,
+    RadialGradient,
+    Select,
+    ToonRamp,
+    Refract,
+    UnpackNormal,
+    Luminance,
+    Flicker,
+    Contrast,
+    Bilerp,
+    Blend,
+    RoughnessToAlpha,
+    FrameTime,
+    FrameCount,
+    RandomSeed,
+    HueShift,
+    Premultiply,
+    Unpremultiply,
 }
 
 /// The response type is used to encode side-effects produced when drawing a
@@ -88,6 +277,167 @@ pub enum MyNodeTemplate {
 pub enum MyResponse {
     SetActiveNode(NodeId),
     ClearActiveNode,
+    Branch(NodeId),
+    CustomDataChanged,
+}
+
+/// Some nodes need to store data that can't be expressed as an input
+/// parameter, and needs to be edited from the node's `bottom_ui`. Since
+/// `bottom_ui` only gets a shared reference to the node data, this data lives
+/// in the graph state instead, keyed by node.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub enum MyNodeCustomData {
+    Curve(Curve),
+    WrapMode(WrapMode),
+    MathConstant(MathConstant),
+    AlphaMode(AlphaMode),
+    BlendMode(BlendMode),
+}
+
+/// How coordinates outside of the `[0, 1]` range are brought back into it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub enum WrapMode {
+    Repeat,
+    Mirror,
+    Clamp,
+}
+
+impl WrapMode {
+    pub const ALL: [WrapMode; 3] = [WrapMode::Repeat, WrapMode::Mirror, WrapMode::Clamp];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            WrapMode::Repeat => "Repeat",
+            WrapMode::Mirror => "Mirror",
+            WrapMode::Clamp => "Clamp",
+        }
+    }
+
+    /// Wraps a single coordinate into the `[0, 1]` range.
+    pub fn apply(self, x: f32) -> f32 {
+        match self {
+            WrapMode::Repeat => x - x.floor(),
+            // Every other repetition is flipped, so the result is continuous.
+            WrapMode::Mirror => {
+                let t = x.rem_euclid(2.0);
+                1.0 - (t - 1.0).abs()
+            }
+            WrapMode::Clamp => x.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// A well-known mathematical constant, output by the math constant node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub enum MathConstant {
+    Pi,
+    Tau,
+    E,
+    Sqrt2,
+}
+
+impl MathConstant {
+    pub const ALL: [MathConstant; 4] = [
+        MathConstant::Pi,
+        MathConstant::Tau,
+        MathConstant::E,
+        MathConstant::Sqrt2,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MathConstant::Pi => "Pi",
+            MathConstant::Tau => "Tau",
+            MathConstant::E => "E",
+            MathConstant::Sqrt2 => "Sqrt 2",
+        }
+    }
+
+    pub fn value(self) -> f32 {
+        match self {
+            MathConstant::Pi => std::f32::consts::PI,
+            MathConstant::Tau => std::f32::consts::TAU,
+            MathConstant::E => std::f32::consts::E,
+            MathConstant::Sqrt2 => std::f32::consts::SQRT_2,
+        }
+    }
+}
+
+/// How the combine alpha node merges its two inputs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub enum AlphaMode {
+    Min,
+    Max,
+    Multiply,
+    Screen,
+}
+
+impl AlphaMode {
+    pub const ALL: [AlphaMode; 4] = [
+        AlphaMode::Min,
+        AlphaMode::Max,
+        AlphaMode::Multiply,
+        AlphaMode::Screen,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AlphaMode::Min => "Min",
+            AlphaMode::Max => "Max",
+            AlphaMode::Multiply => "Multiply",
+            AlphaMode::Screen => "Screen",
+        }
+    }
+
+    pub fn apply(self, a: f32, b: f32) -> f32 {
+        match self {
+            AlphaMode::Min => a.min(b),
+            AlphaMode::Max => a.max(b),
+            AlphaMode::Multiply => a * b,
+            AlphaMode::Screen => 1.0 - (1.0 - a) * (1.0 - b),
+        }
+    }
+}
+
+/// How the blend node combines the channels of its two colors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub enum BlendMode {
+    Multiply,
+    Screen,
+    Overlay,
+}
+
+impl BlendMode {
+    pub const ALL: [BlendMode; 3] = [BlendMode::Multiply, BlendMode::Screen, BlendMode::Overlay];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BlendMode::Multiply => "Multiply",
+            BlendMode::Screen => "Screen",
+            BlendMode::Overlay => "Overlay",
+        }
+    }
+
+    /// Blends a single channel of `b` over `a`.
+    pub fn apply(self, a: f32, b: f32) -> f32 {
+        match self {
+            BlendMode::Multiply => a * b,
+            BlendMode::Screen => 1.0 - (1.0 - a) * (1.0 - b),
+            // Multiplies the darks and screens the lights of the base color.
+            BlendMode::Overlay => {
+                if a < 0.5 {
+                    2.0 * a * b
+                } else {
+                    1.0 - 2.0 * (1.0 - a) * (1.0 - b)
+                }
+            }
+        }
+    }
 }
 
 /// The graph 'global' state. This state struct is passed around to the node and
@@ -97,6 +447,10 @@ pub enum MyResponse {
 #[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
 pub struct MyGraphState {
     pub active_node: Option<NodeId>,
+    pub node_custom_data: HashMap<NodeId, MyNodeCustomData>,
+    /// When set, only these nodes are drawn normally and the rest are faded out.
+    #[cfg_attr(feature = "persistence", serde(skip))]
+    pub focused_nodes: Option<HashSet<NodeId>>,
 }
 
 // =========== Then, you need to implement some traits ============
@@ -107,6 +461,9 @@ impl DataTypeTrait<MyGraphState> for MyDataType {
         match self {
             MyDataType::Scalar => egui::Color32::from_rgb(38, 109, 211),
             MyDataType::Vec2 => egui::Color32::from_rgb(238, 207, 109),
+            MyDataType::Vec4 => egui::Color32::from_rgb(207, 91, 177),
+            MyDataType::Color => egui::Color32::from_rgb(235, 110, 80),
+            MyDataType::Bool => egui::Color32::from_rgb(113, 189, 102),
         }
     }
 
@@ -114,8 +471,19 @@ impl DataTypeTrait<MyGraphState> for MyDataType {
         match self {
             MyDataType::Scalar => Cow::Borrowed("scalar"),
             MyDataType::Vec2 => Cow::Borrowed("2d vector"),
+            MyDataType::Vec4 => Cow::Borrowed("4d vector"),
+            MyDataType::Color => Cow::Borrowed("color"),
+            MyDataType::Bool => Cow::Borrowed("bool"),
         }
     }
+
+    // Any output can be connected to any input. The values are converted when
+    // evaluating the graph, see `MyValueType::convert_to`: booleans become 0 or
+    // 1 (in every component), and other values are true when their first
+    // component is nonzero.
+    fn can_connect_to(&self, _input: &Self) -> bool {
+        true
+    }
 }
 
 // A trait for the node kinds, which tells the library how to build new nodes
@@ -136,6 +504,69 @@ impl NodeTemplateTrait for MyNodeTemplate {
             MyNodeTemplate::AddVector => "Vector add",
             MyNodeTemplate::SubtractVector => "Vector subtract",
             MyNodeTemplate::VectorTimesScalar => "Vector times scalar",
+            MyNodeTemplate::CurveRemap => "Curve remap",
+            MyNodeTemplate::PolarCoordinates => "Polar coordinates",
+            MyNodeTemplate::GammaCorrect => "Gamma correct",
+            MyNodeTemplate::TimeWrap => "Time wrap",
+            MyNodeTemplate::SoftClamp => "Soft clamp",
+            MyNodeTemplate::Caustics => "Caustics",
+            MyNodeTemplate::Clamp => "Clamp",
+            MyNodeTemplate::ClampVector => "Vector clamp",
+            MyNodeTemplate::Abs => "Abs",
+            MyNodeTemplate::AbsVector => "Vector abs",
+            MyNodeTemplate::Floor => "Floor",
+            MyNodeTemplate::Ceil => "Ceil",
+            MyNodeTemplate::Round => "Round",
+            MyNodeTemplate::FloorVector => "Vector floor",
+            MyNodeTemplate::CeilVector => "Vector ceil",
+            MyNodeTemplate::RoundVector => "Vector round",
+            MyNodeTemplate::Frac => "Frac",
+            MyNodeTemplate::FracVector => "Vector frac",
+            MyNodeTemplate::WrapUv => "Wrap UV",
+            MyNodeTemplate::Mod => "Mod",
+            MyNodeTemplate::Exp => "Exp",
+            MyNodeTemplate::Log => "Log",
+            MyNodeTemplate::Log2 => "Log2",
+            MyNodeTemplate::MathConstant => "Math constant",
+            MyNodeTemplate::Pulse => "Pulse",
+            MyNodeTemplate::Tan => "Tan",
+            MyNodeTemplate::Asin => "Asin",
+            MyNodeTemplate::Acos => "Acos",
+            MyNodeTemplate::Atan => "Atan",
+            MyNodeTemplate::Atan2 => "Atan2",
+            MyNodeTemplate::Length => "Length",
+            MyNodeTemplate::Distance => "Distance",
+            MyNodeTemplate::Rsqrt => "Rsqrt",
+            MyNodeTemplate::OneMinus => "One minus",
+            MyNodeTemplate::OneMinusVector => "Vector one minus",
+            MyNodeTemplate::Remap => "Remap",
+            MyNodeTemplate::ThreeStopGradient => "Three stop gradient",
+            MyNodeTemplate::Posterize => "Posterize",
+            MyNodeTemplate::PosterizeVector => "Vector posterize",
+            MyNodeTemplate::FlowDistort => "Flow distort",
+            MyNodeTemplate::Panner => "Panner",
+            MyNodeTemplate::UvDebug => "UV debug",
+            MyNodeTemplate::MakeVec4 => "New vec4",
+            MyNodeTemplate::CombineAlpha => "Combine alpha",
+            MyNodeTemplate::MakeColor => "New color",
+            MyNodeTemplate::SignedPow => "Signed pow",
+            MyNodeTemplate::RadialGradient => "Radial gradient",
+            MyNodeTemplate::Select => "Select",
+            MyNodeTemplate::ToonRamp => "Toon ramp",
+            MyNodeTemplate::Refract => "Refract",
+            MyNodeTemplate::UnpackNormal => "Unpack normal",
+            MyNodeTemplate::Luminance => "Luminance",
+            MyNodeTemplate::Flicker => "Flicker",
+            MyNodeTemplate::Contrast => "Contrast",
+            MyNodeTemplate::Bilerp => "Bilinear blend",
+            MyNodeTemplate::Blend => "Blend",
+            MyNodeTemplate::RoughnessToAlpha => "Roughness to alpha",
+            MyNodeTemplate::FrameTime => "Frame time",
+            MyNodeTemplate::FrameCount => "Frame count",
+            MyNodeTemplate::RandomSeed => "Random seed",
+            MyNodeTemplate::HueShift => "Hue shift",
+            MyNodeTemplate::Premultiply => "Premultiply alpha",
+            MyNodeTemplate::Unpremultiply => "Unpremultiply alpha",
         })
     }
 
@@ -144,11 +575,74 @@ impl NodeTemplateTrait for MyNodeTemplate {
         match self {
             MyNodeTemplate::MakeScalar
             | MyNodeTemplate::AddScalar
-            | MyNodeTemplate::SubtractScalar => vec!["Scalar"],
+            | MyNodeTemplate::SubtractScalar
+            | MyNodeTemplate::CurveRemap
+            | MyNodeTemplate::GammaCorrect
+            | MyNodeTemplate::TimeWrap
+            | MyNodeTemplate::SoftClamp
+            | MyNodeTemplate::Clamp
+            | MyNodeTemplate::Abs
+            | MyNodeTemplate::Floor
+            | MyNodeTemplate::Ceil
+            | MyNodeTemplate::Round
+            | MyNodeTemplate::Frac
+            | MyNodeTemplate::Mod
+            | MyNodeTemplate::Exp
+            | MyNodeTemplate::Log
+            | MyNodeTemplate::Log2
+            | MyNodeTemplate::MathConstant
+            | MyNodeTemplate::Pulse
+            | MyNodeTemplate::Tan
+            | MyNodeTemplate::Asin
+            | MyNodeTemplate::Acos
+            | MyNodeTemplate::Atan
+            | MyNodeTemplate::Atan2
+            | MyNodeTemplate::Rsqrt
+            | MyNodeTemplate::OneMinus
+            | MyNodeTemplate::Remap
+            | MyNodeTemplate::Posterize
+            | MyNodeTemplate::CombineAlpha
+            | MyNodeTemplate::SignedPow
+            | MyNodeTemplate::Select
+            | MyNodeTemplate::RoughnessToAlpha
+            | MyNodeTemplate::FrameTime
+            | MyNodeTemplate::FrameCount => vec!["Scalar"],
             MyNodeTemplate::MakeVector
             | MyNodeTemplate::AddVector
-            | MyNodeTemplate::SubtractVector => vec!["Vector"],
+            | MyNodeTemplate::SubtractVector
+            | MyNodeTemplate::PolarCoordinates
+            | MyNodeTemplate::ClampVector
+            | MyNodeTemplate::AbsVector
+            | MyNodeTemplate::FloorVector
+            | MyNodeTemplate::CeilVector
+            | MyNodeTemplate::RoundVector
+            | MyNodeTemplate::FracVector
+            | MyNodeTemplate::WrapUv
+            | MyNodeTemplate::Length
+            | MyNodeTemplate::Distance
+            | MyNodeTemplate::OneMinusVector
+            | MyNodeTemplate::PosterizeVector
+            | MyNodeTemplate::FlowDistort
+            | MyNodeTemplate::Panner
+            | MyNodeTemplate::MakeVec4
+            | MyNodeTemplate::Refract
+            | MyNodeTemplate::UnpackNormal => vec!["Vector"],
             MyNodeTemplate::VectorTimesScalar => vec!["Vector", "Scalar"],
+            MyNodeTemplate::Caustics
+            | MyNodeTemplate::UvDebug
+            | MyNodeTemplate::RadialGradient
+            | MyNodeTemplate::Flicker
+            | MyNodeTemplate::RandomSeed => vec!["Procedural"],
+            MyNodeTemplate::MakeColor
+            | MyNodeTemplate::ToonRamp
+            | MyNodeTemplate::Luminance
+            | MyNodeTemplate::Contrast
+            | MyNodeTemplate::Bilerp
+            | MyNodeTemplate::Blend
+            | MyNodeTemplate::HueShift
+            | MyNodeTemplate::Premultiply
+            | MyNodeTemplate::Unpremultiply
+            | MyNodeTemplate::ThreeStopGradient => vec!["Color"],
         }
     }
 
@@ -165,32 +659,61 @@ impl NodeTemplateTrait for MyNodeTemplate {
     fn build_node(
         &self,
         graph: &mut Graph<Self::NodeData, Self::DataType, Self::ValueType>,
-        _user_state: &mut Self::UserState,
+        user_state: &mut Self::UserState,
         node_id: NodeId,
     ) {
         // The nodes are created empty by default. This function needs to take
         // care of creating the desired inputs and outputs based on the template
 
         // We define some closures here to avoid boilerplate. Note that this is
-        // entirely optional.
-        let input_scalar = |graph: &mut MyGraph, name: &str| {
+        // entirely optional. The last argument is the default value of the
+        // parameter, used while the input is not connected.
+        let input_scalar = |graph: &mut MyGraph, name: &str, value: f32| {
             graph.add_input_param(
                 node_id,
                 name.to_string(),
                 MyDataType::Scalar,
-                MyValueType::Scalar { value: 0.0 },
+                MyValueType::Scalar { value },
                 InputParamKind::ConnectionOrConstant,
                 true,
             );
         };
-        let input_vector = |graph: &mut MyGraph, name: &str| {
+        let input_vector = |graph: &mut MyGraph, name: &str, value: egui::Vec2| {
             graph.add_input_param(
                 node_id,
                 name.to_string(),
                 MyDataType::Vec2,
-                MyValueType::Vec2 {
-                    value: egui::vec2(0.0, 0.0),
-                },
+                MyValueType::Vec2 { value },
+                InputParamKind::ConnectionOrConstant,
+                true,
+            );
+        };
+        let input_vec4 = |graph: &mut MyGraph, name: &str, value: [f32; 4]| {
+            graph.add_input_param(
+                node_id,
+                name.to_string(),
+                MyDataType::Vec4,
+                MyValueType::Vec4 { value },
+                InputParamKind::ConnectionOrConstant,
+                true,
+            );
+        };
+        let input_color = |graph: &mut MyGraph, name: &str, value: [f32; 3]| {
+            graph.add_input_param(
+                node_id,
+                name.to_string(),
+                MyDataType::Color,
+                MyValueType::Color { value },
+                InputParamKind::ConnectionOrConstant,
+                true,
+            );
+        };
+        let input_bool = |graph: &mut MyGraph, name: &str, value: bool| {
+            graph.add_input_param(
+                node_id,
+                name.to_string(),
+                MyDataType::Bool,
+                MyValueType::Bool { value },
                 InputParamKind::ConnectionOrConstant,
                 true,
             );
@@ -202,6 +725,12 @@ impl NodeTemplateTrait for MyNodeTemplate {
         let output_vector = |graph: &mut MyGraph, name: &str| {
             graph.add_output_param(node_id, name.to_string(), MyDataType::Vec2);
         };
+        let output_vec4 = |graph: &mut MyGraph, name: &str| {
+            graph.add_output_param(node_id, name.to_string(), MyDataType::Vec4);
+        };
+        let output_color = |graph: &mut MyGraph, name: &str| {
+            graph.add_output_param(node_id, name.to_string(), MyDataType::Color);
+        };
 
         match self {
             MyNodeTemplate::AddScalar => {
@@ -222,38 +751,310 @@ impl NodeTemplateTrait for MyNodeTemplate {
                     InputParamKind::ConnectionOrConstant,
                     true,
                 );
-                input_scalar(graph, "B");
+                input_scalar(graph, "B", 0.0);
                 output_scalar(graph, "out");
             }
             MyNodeTemplate::SubtractScalar => {
-                input_scalar(graph, "A");
-                input_scalar(graph, "B");
+                input_scalar(graph, "A", 0.0);
+                input_scalar(graph, "B", 0.0);
                 output_scalar(graph, "out");
             }
             MyNodeTemplate::VectorTimesScalar => {
-                input_scalar(graph, "scalar");
-                input_vector(graph, "vector");
+                input_scalar(graph, "scalar", 0.0);
+                input_vector(graph, "vector", egui::Vec2::ZERO);
                 output_vector(graph, "out");
             }
             MyNodeTemplate::AddVector => {
-                input_vector(graph, "v1");
-                input_vector(graph, "v2");
+                input_vector(graph, "v1", egui::Vec2::ZERO);
+                input_vector(graph, "v2", egui::Vec2::ZERO);
                 output_vector(graph, "out");
             }
             MyNodeTemplate::SubtractVector => {
-                input_vector(graph, "v1");
-                input_vector(graph, "v2");
+                input_vector(graph, "v1", egui::Vec2::ZERO);
+                input_vector(graph, "v2", egui::Vec2::ZERO);
                 output_vector(graph, "out");
             }
             MyNodeTemplate::MakeVector => {
-                input_scalar(graph, "x");
-                input_scalar(graph, "y");
+                input_scalar(graph, "x", 0.0);
+                input_scalar(graph, "y", 0.0);
                 output_vector(graph, "out");
             }
             MyNodeTemplate::MakeScalar => {
-                input_scalar(graph, "value");
+                input_scalar(graph, "value", 0.0);
+                output_scalar(graph, "out");
+            }
+            MyNodeTemplate::CurveRemap => {
+                input_scalar(graph, "x", 0.0);
+                output_scalar(graph, "out");
+            }
+            MyNodeTemplate::PolarCoordinates => {
+                input_vector(graph, "uv", egui::Vec2::ZERO);
+                input_vector(graph, "center", egui::vec2(0.5, 0.5));
+                input_scalar(graph, "radial scale", 1.0);
+                input_scalar(graph, "length scale", 1.0);
+                output_vector(graph, "out");
+            }
+            MyNodeTemplate::GammaCorrect => {
+                input_scalar(graph, "x", 0.0);
+                input_scalar(graph, "gamma", 2.2);
+                output_scalar(graph, "out");
+            }
+            MyNodeTemplate::TimeWrap => {
+                input_scalar(graph, "t", 0.0);
+                input_scalar(graph, "period", std::f32::consts::TAU);
+                output_scalar(graph, "out");
+            }
+            MyNodeTemplate::SoftClamp => {
+                input_scalar(graph, "x", 0.0);
+                input_scalar(graph, "knee", 0.2);
+                output_scalar(graph, "out");
+            }
+            MyNodeTemplate::Caustics => {
+                input_vector(graph, "uv", egui::Vec2::ZERO);
+                input_scalar(graph, "time", 0.0);
+                input_scalar(graph, "scale", 4.0);
+                output_scalar(graph, "out");
+            }
+            MyNodeTemplate::Clamp => {
+                input_scalar(graph, "x", 0.0);
+                input_scalar(graph, "min", 0.0);
+                input_scalar(graph, "max", 1.0);
+                output_scalar(graph, "out");
+            }
+            MyNodeTemplate::ClampVector => {
+                input_vector(graph, "v", egui::Vec2::ZERO);
+                input_vector(graph, "min", egui::Vec2::ZERO);
+                input_vector(graph, "max", egui::Vec2::splat(1.0));
+                output_vector(graph, "out");
+            }
+            MyNodeTemplate::Abs
+            | MyNodeTemplate::Floor
+            | MyNodeTemplate::Ceil
+            | MyNodeTemplate::Round
+            | MyNodeTemplate::Frac
+            | MyNodeTemplate::Exp
+            | MyNodeTemplate::Log
+            | MyNodeTemplate::Log2
+            | MyNodeTemplate::Tan
+            | MyNodeTemplate::Asin
+            | MyNodeTemplate::Acos
+            | MyNodeTemplate::Atan
+            | MyNodeTemplate::Rsqrt
+            | MyNodeTemplate::OneMinus => {
+                input_scalar(graph, "x", 0.0);
+                output_scalar(graph, "out");
+            }
+            MyNodeTemplate::AbsVector
+            | MyNodeTemplate::FloorVector
+            | MyNodeTemplate::CeilVector
+            | MyNodeTemplate::RoundVector
+            | MyNodeTemplate::FracVector
+            | MyNodeTemplate::OneMinusVector => {
+                input_vector(graph, "v", egui::Vec2::ZERO);
+                output_vector(graph, "out");
+            }
+            MyNodeTemplate::WrapUv => {
+                input_vector(graph, "uv", egui::Vec2::ZERO);
+                output_vector(graph, "out");
+            }
+            MyNodeTemplate::Mod => {
+                input_scalar(graph, "a", 0.0);
+                // Defaults to one, so the node doesn't produce NaN while `b` is unset.
+                input_scalar(graph, "b", 1.0);
+                output_scalar(graph, "out");
+            }
+            MyNodeTemplate::MathConstant => {
+                output_scalar(graph, "out");
+            }
+            MyNodeTemplate::Pulse => {
+                input_scalar(graph, "x", 0.0);
+                input_scalar(graph, "center", 0.5);
+                input_scalar(graph, "width", 0.2);
+                output_scalar(graph, "out");
+            }
+            MyNodeTemplate::Atan2 => {
+                input_scalar(graph, "y", 0.0);
+                input_scalar(graph, "x", 1.0);
+                output_scalar(graph, "out");
+            }
+            MyNodeTemplate::Length => {
+                input_vector(graph, "v", egui::Vec2::ZERO);
+                output_scalar(graph, "out");
+            }
+            MyNodeTemplate::Distance => {
+                input_vector(graph, "a", egui::Vec2::ZERO);
+                input_vector(graph, "b", egui::Vec2::ZERO);
+                output_scalar(graph, "out");
+            }
+            MyNodeTemplate::Remap => {
+                input_scalar(graph, "x", 0.0);
+                input_scalar(graph, "in min", 0.0);
+                input_scalar(graph, "in max", 1.0);
+                input_scalar(graph, "out min", 0.0);
+                input_scalar(graph, "out max", 1.0);
                 output_scalar(graph, "out");
             }
+            MyNodeTemplate::ThreeStopGradient => {
+                input_scalar(graph, "t", 0.0);
+                input_color(graph, "a", [0.0, 0.0, 0.0]);
+                input_color(graph, "b", [0.5, 0.5, 0.5]);
+                input_color(graph, "c", [1.0, 1.0, 1.0]);
+                output_color(graph, "out");
+            }
+            MyNodeTemplate::Posterize => {
+                input_scalar(graph, "x", 0.0);
+                input_scalar(graph, "steps", 4.0);
+                output_scalar(graph, "out");
+            }
+            MyNodeTemplate::PosterizeVector => {
+                input_vector(graph, "v", egui::Vec2::ZERO);
+                input_scalar(graph, "steps", 4.0);
+                output_vector(graph, "out");
+            }
+            MyNodeTemplate::FlowDistort => {
+                input_vector(graph, "uv", egui::Vec2::ZERO);
+                // Encodes a flow of zero, see the evaluation below.
+                input_vector(graph, "flow", egui::vec2(0.5, 0.5));
+                input_scalar(graph, "strength", 0.1);
+                input_scalar(graph, "time", 0.0);
+                output_vector(graph, "out");
+            }
+            MyNodeTemplate::Panner => {
+                input_vector(graph, "uv", egui::Vec2::ZERO);
+                input_vector(graph, "speed", egui::vec2(0.1, 0.0));
+                input_scalar(graph, "time", 0.0);
+                output_vector(graph, "out");
+            }
+            MyNodeTemplate::UvDebug => {
+                input_vector(graph, "uv", egui::Vec2::ZERO);
+                output_vector(graph, "out");
+            }
+            MyNodeTemplate::MakeVec4 => {
+                input_scalar(graph, "r", 0.0);
+                input_scalar(graph, "g", 0.0);
+                input_scalar(graph, "b", 0.0);
+                input_scalar(graph, "a", 1.0);
+                output_vec4(graph, "out");
+            }
+            MyNodeTemplate::CombineAlpha => {
+                input_scalar(graph, "a", 1.0);
+                input_scalar(graph, "b", 1.0);
+                output_scalar(graph, "out");
+            }
+            MyNodeTemplate::MakeColor => {
+                input_color(graph, "color", [1.0, 1.0, 1.0]);
+                output_color(graph, "out");
+            }
+            MyNodeTemplate::SignedPow => {
+                input_scalar(graph, "x", 0.0);
+                input_scalar(graph, "exp", 1.0);
+                output_scalar(graph, "out");
+            }
+            MyNodeTemplate::RadialGradient => {
+                input_vector(graph, "uv", egui::Vec2::ZERO);
+                input_vector(graph, "center", egui::vec2(0.5, 0.5));
+                input_scalar(graph, "radius", 0.5);
+                input_scalar(graph, "softness", 0.2);
+                output_scalar(graph, "out");
+            }
+            MyNodeTemplate::Select => {
+                input_bool(graph, "condition", true);
+                input_scalar(graph, "if true", 1.0);
+                input_scalar(graph, "if false", 0.0);
+                output_scalar(graph, "out");
+            }
+            MyNodeTemplate::ToonRamp => {
+                input_scalar(graph, "ndl", 0.0);
+                input_scalar(graph, "threshold 1", 0.3);
+                input_scalar(graph, "threshold 2", 0.6);
+                input_color(graph, "shadow", [0.2, 0.2, 0.3]);
+                input_color(graph, "mid", [0.6, 0.6, 0.7]);
+                input_color(graph, "light", [1.0, 1.0, 1.0]);
+                output_color(graph, "out");
+            }
+            MyNodeTemplate::Refract => {
+                input_vector(graph, "I", egui::vec2(0.0, -1.0));
+                input_vector(graph, "N", egui::vec2(0.0, 1.0));
+                input_scalar(graph, "eta", 1.0);
+                output_vector(graph, "out");
+            }
+            MyNodeTemplate::UnpackNormal => {
+                input_vector(graph, "packed", egui::vec2(0.5, 0.5));
+                output_vector(graph, "out");
+            }
+            MyNodeTemplate::Luminance => {
+                input_color(graph, "color", [1.0, 1.0, 1.0]);
+                output_scalar(graph, "out");
+            }
+            MyNodeTemplate::Flicker => {
+                input_scalar(graph, "time", 0.0);
+                input_scalar(graph, "frequency", 8.0);
+                input_scalar(graph, "seed", 0.0);
+                output_scalar(graph, "out");
+            }
+            MyNodeTemplate::Contrast => {
+                input_color(graph, "color", [1.0, 1.0, 1.0]);
+                // An amount of one leaves the color untouched.
+                input_scalar(graph, "amount", 1.0);
+                output_color(graph, "out");
+            }
+            MyNodeTemplate::Bilerp => {
+                input_color(graph, "bottom left", [0.0, 0.0, 0.0]);
+                input_color(graph, "bottom right", [1.0, 0.0, 0.0]);
+                input_color(graph, "top left", [0.0, 1.0, 0.0]);
+                input_color(graph, "top right", [1.0, 1.0, 0.0]);
+                input_scalar(graph, "u", 0.5);
+                input_scalar(graph, "v", 0.5);
+                output_color(graph, "out");
+            }
+            MyNodeTemplate::Blend => {
+                input_color(graph, "a", [1.0, 1.0, 1.0]);
+                input_color(graph, "b", [1.0, 1.0, 1.0]);
+                input_scalar(graph, "t", 1.0);
+                output_color(graph, "out");
+            }
+            MyNodeTemplate::RoughnessToAlpha => {
+                input_scalar(graph, "roughness", 0.5);
+                output_scalar(graph, "out");
+            }
+            MyNodeTemplate::FrameTime | MyNodeTemplate::FrameCount => {
+                input_scalar(graph, "time", 0.0);
+                input_scalar(graph, "fps", 30.0);
+                output_scalar(graph, "out");
+            }
+            MyNodeTemplate::RandomSeed => {
+                input_scalar(graph, "time", 0.0);
+                output_scalar(graph, "out");
+            }
+            MyNodeTemplate::HueShift => {
+                input_color(graph, "color", [1.0, 0.0, 0.0]);
+                input_scalar(graph, "time", 0.0);
+                input_scalar(graph, "speed", 0.1);
+                output_color(graph, "out");
+            }
+            MyNodeTemplate::Premultiply | MyNodeTemplate::Unpremultiply => {
+                input_vec4(graph, "color", [1.0, 1.0, 1.0, 1.0]);
+                output_vec4(graph, "out");
+            }
+        }
+
+        // Some nodes also have data that is edited in their bottom UI.
+        if let Some(custom_data) = self.default_custom_data() {
+            user_state.node_custom_data.insert(node_id, custom_data);
+        }
+    }
+}
+
+impl MyNodeTemplate {
+    /// The custom data for new nodes of this template, if they need any.
+    fn default_custom_data(self) -> Option<MyNodeCustomData> {
+        match self {
+            MyNodeTemplate::CurveRemap => Some(MyNodeCustomData::Curve(Curve::default())),
+            MyNodeTemplate::WrapUv => Some(MyNodeCustomData::WrapMode(WrapMode::Repeat)),
+            MyNodeTemplate::MathConstant => Some(MyNodeCustomData::MathConstant(MathConstant::Pi)),
+            MyNodeTemplate::CombineAlpha => Some(MyNodeCustomData::AlphaMode(AlphaMode::Multiply)),
+            MyNodeTemplate::Blend => Some(MyNodeCustomData::BlendMode(BlendMode::Multiply)),
+            _ => None,
         }
     }
 }
@@ -274,6 +1075,69 @@ impl NodeTemplateIter for AllMyNodeTemplates {
             MyNodeTemplate::AddVector,
             MyNodeTemplate::SubtractVector,
             MyNodeTemplate::VectorTimesScalar,
+            MyNodeTemplate::CurveRemap,
+            MyNodeTemplate::PolarCoordinates,
+            MyNodeTemplate::GammaCorrect,
+            MyNodeTemplate::TimeWrap,
+            MyNodeTemplate::SoftClamp,
+            MyNodeTemplate::Caustics,
+            MyNodeTemplate::Clamp,
+            MyNodeTemplate::ClampVector,
+            MyNodeTemplate::Abs,
+            MyNodeTemplate::AbsVector,
+            MyNodeTemplate::Floor,
+            MyNodeTemplate::Ceil,
+            MyNodeTemplate::Round,
+            MyNodeTemplate::FloorVector,
+            MyNodeTemplate::CeilVector,
+            MyNodeTemplate::RoundVector,
+            MyNodeTemplate::Frac,
+            MyNodeTemplate::FracVector,
+            MyNodeTemplate::WrapUv,
+            MyNodeTemplate::Mod,
+            MyNodeTemplate::Exp,
+            MyNodeTemplate::Log,
+            MyNodeTemplate::Log2,
+            MyNodeTemplate::MathConstant,
+            MyNodeTemplate::Pulse,
+            MyNodeTemplate::Tan,
+            MyNodeTemplate::Asin,
+            MyNodeTemplate::Acos,
+            MyNodeTemplate::Atan,
+            MyNodeTemplate::Atan2,
+            MyNodeTemplate::Length,
+            MyNodeTemplate::Distance,
+            MyNodeTemplate::Rsqrt,
+            MyNodeTemplate::OneMinus,
+            MyNodeTemplate::OneMinusVector,
+            MyNodeTemplate::Remap,
+            MyNodeTemplate::ThreeStopGradient,
+            MyNodeTemplate::Posterize,
+            MyNodeTemplate::PosterizeVector,
+            MyNodeTemplate::FlowDistort,
+            MyNodeTemplate::Panner,
+            MyNodeTemplate::UvDebug,
+            MyNodeTemplate::MakeVec4,
+            MyNodeTemplate::CombineAlpha,
+            MyNodeTemplate::MakeColor,
+            MyNodeTemplate::SignedPow,
+            MyNodeTemplate::RadialGradient,
+            MyNodeTemplate::Select,
+            MyNodeTemplate::ToonRamp,
+            MyNodeTemplate::Refract,
+            MyNodeTemplate::UnpackNormal,
+            MyNodeTemplate::Luminance,
+            MyNodeTemplate::Flicker,
+            MyNodeTemplate::Contrast,
+            MyNodeTemplate::Bilerp,
+            MyNodeTemplate::Blend,
+            MyNodeTemplate::RoughnessToAlpha,
+            MyNodeTemplate::FrameTime,
+            MyNodeTemplate::FrameCount,
+            MyNodeTemplate::RandomSeed,
+            MyNodeTemplate::HueShift,
+            MyNodeTemplate::Premultiply,
+            MyNodeTemplate::Unpremultiply,
         ]
     }
 }
@@ -308,6 +1172,24 @@ impl WidgetValueTrait for MyValueType {
                     ui.add(DragValue::new(value));
                 });
             }
+            MyValueType::Vec4 { value } => {
+                ui.label(param_name);
+                ui.horizontal(|ui| {
+                    for (label, component) in ["r", "g", "b", "a"].into_iter().zip(value) {
+                        ui.label(label);
+                        ui.add(DragValue::new(component).speed(0.01));
+                    }
+                });
+            }
+            MyValueType::Color { value } => {
+                ui.horizontal(|ui| {
+                    ui.label(param_name);
+                    egui::color_picker::color_edit_button_rgb(ui, value);
+                });
+            }
+            MyValueType::Bool { value } => {
+                ui.checkbox(value, param_name);
+            }
         }
         // This allows you to return your responses from the inline widgets.
         Vec::new()
@@ -342,6 +1224,54 @@ impl NodeDataTrait for MyNodeData {
         // UIs based on that.
 
         let mut responses = vec![];
+
+        // Nodes with custom data get to edit it here. The data is created along
+        // with the node, but may be missing if the user state failed to load,
+        // so it is recreated when needed. The default data is only built in
+        // that case, since it may allocate.
+        if let Entry::Vacant(entry) = user_state.node_custom_data.entry(node_id) {
+            if let Some(custom_data) = self.template.default_custom_data() {
+                entry.insert(custom_data);
+            }
+        }
+        let changed = match user_state.node_custom_data.get_mut(&node_id) {
+            Some(MyNodeCustomData::Curve(curve)) => {
+                curve_editor(ui, egui::Id::new((node_id, "curve")), curve)
+            }
+            Some(MyNodeCustomData::WrapMode(mode)) => option_combo_box(
+                ui,
+                (node_id, "wrap mode"),
+                mode,
+                WrapMode::ALL,
+                WrapMode::label,
+            ),
+            Some(MyNodeCustomData::MathConstant(constant)) => option_combo_box(
+                ui,
+                (node_id, "math constant"),
+                constant,
+                MathConstant::ALL,
+                MathConstant::label,
+            ),
+            Some(MyNodeCustomData::AlphaMode(mode)) => option_combo_box(
+                ui,
+                (node_id, "alpha mode"),
+                mode,
+                AlphaMode::ALL,
+                AlphaMode::label,
+            ),
+            Some(MyNodeCustomData::BlendMode(mode)) => option_combo_box(
+                ui,
+                (node_id, "blend mode"),
+                mode,
+                BlendMode::ALL,
+                BlendMode::label,
+            ),
+            None => false,
+        };
+        if changed {
+            responses.push(NodeResponse::User(MyResponse::CustomDataChanged));
+        }
+
         let is_active = user_state
             .active_node
             .map(|id| id == node_id)
@@ -364,14 +1294,76 @@ impl NodeDataTrait for MyNodeData {
             }
         }
 
+        // Branching creates a copy of this node fed by the same inputs, so
+        // the two copies can be tweaked independently.
+        if ui.button("Branch").clicked() {
+            responses.push(NodeResponse::User(MyResponse::Branch(node_id)));
+        }
+
         responses
     }
+
+    // Nodes outside of the focused set, if any, are faded out.
+    fn faded(
+        &self,
+        node_id: NodeId,
+        _graph: &Graph<MyNodeData, MyDataType, MyValueType>,
+        user_state: &mut Self::UserState,
+    ) -> bool {
+        match &user_state.focused_nodes {
+            Some(focused) => !focused.contains(&node_id),
+            None => false,
+        }
+    }
+}
+
+/// Draws a combo box to pick one of the given options. Returns true when a
+/// different option was picked.
+fn option_combo_box<T: Copy + PartialEq>(
+    ui: &mut egui::Ui,
+    id_source: impl std::hash::Hash,
+    value: &mut T,
+    options: impl IntoIterator<Item = T>,
+    label: fn(T) -> &'static str,
+) -> bool {
+    let mut changed = false;
+    egui::ComboBox::from_id_source(id_source)
+        .selected_text(label(*value))
+        .show_ui(ui, |ui| {
+            for option in options {
+                if ui.selectable_value(value, option, label(option)).changed() {
+                    changed = true;
+                }
+            }
+        });
+    changed
 }
 
 type MyGraph = Graph<MyNodeData, MyDataType, MyValueType>;
 type MyEditorState =
     GraphEditorState<MyNodeData, MyDataType, MyValueType, MyNodeTemplate, MyGraphState>;
 
+/// Editor preferences. Unlike the graph state, these are not tied to any
+/// particular graph.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub struct MyUiPrefs {
+    /// When true, the active node is evaluated every frame. Otherwise, it is
+    /// only evaluated when the user asks for it.
+    pub auto_evaluate: bool,
+    /// When true, nodes that don't contribute to the active node are faded out.
+    pub focus_active_node: bool,
+}
+
+impl Default for MyUiPrefs {
+    fn default() -> Self {
+        Self {
+            auto_evaluate: true,
+            focus_active_node: false,
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct NodeGraphExample {
     // The `GraphEditorState` is the top-level object. You "register" all your
@@ -379,11 +1371,38 @@ pub struct NodeGraphExample {
     state: MyEditorState,
 
     user_state: MyGraphState,
+
+    prefs: MyUiPrefs,
+
+    /// The text describing the result of the last evaluation of the active node.
+    evaluation_result: Option<String>,
+
+    /// The value used by the "Fill unconnected inputs" action.
+    fill_value: f32,
+
+    /// The state of the "Replace values" window, when open.
+    replace_dialog: Option<ReplaceValues>,
+}
+
+/// Values entered in the "Replace values" window.
+#[derive(Clone, Copy, Default)]
+struct ReplaceValues {
+    find: f32,
+    replace: f32,
 }
 
+/// Constants closer than this to the searched value are considered a match.
+const REPLACE_EPSILON: f32 = 1e-4;
+
 #[cfg(feature = "persistence")]
 const PERSISTENCE_KEY: &str = "egui_node_graph";
 
+#[cfg(feature = "persistence")]
+const USER_STATE_PERSISTENCE_KEY: &str = "egui_node_graph_user_state";
+
+#[cfg(feature = "persistence")]
+const PREFS_PERSISTENCE_KEY: &str = "egui_node_graph_prefs";
+
 #[cfg(feature = "persistence")]
 impl NodeGraphExample {
     /// If the persistence feature is enabled, Called once before the first frame.
@@ -393,28 +1412,259 @@ impl NodeGraphExample {
             .storage
             .and_then(|storage| eframe::get_value(storage, PERSISTENCE_KEY))
             .unwrap_or_default();
+        let user_state = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, USER_STATE_PERSISTENCE_KEY))
+            .unwrap_or_default();
+        let prefs = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, PREFS_PERSISTENCE_KEY))
+            .unwrap_or_default();
         Self {
             state,
-            user_state: MyGraphState::default(),
+            user_state,
+            prefs,
+            evaluation_result: None,
+            fill_value: 0.0,
+            replace_dialog: None,
         }
     }
 }
 
+impl NodeGraphExample {
+    /// Creates a copy of the given node, with the same input values and with
+    /// every input connected to the same output as in the original node. The
+    /// outputs of the copy are left unconnected.
+    fn branch_node(&mut self, node_id: NodeId) -> NodeId {
+        let template = self.state.graph[node_id].user_data.template;
+        let new_node = self.state.graph.add_node(
+            template.node_graph_label(&mut self.user_state),
+            template.user_data(&mut self.user_state),
+            |graph, new_node| template.build_node(graph, &mut self.user_state, new_node),
+        );
+
+        // Both nodes come from the same template, so their inputs match.
+        let inputs = self.state.graph[node_id].inputs.clone();
+        let new_inputs = self.state.graph[new_node].inputs.clone();
+        for ((_, input), (_, new_input)) in inputs.into_iter().zip(new_inputs) {
+            self.state.graph[new_input].value = self.state.graph[input].value;
+            if let Some(output) = self.state.graph.connection(input) {
+                self.state.graph.add_connection(output, new_input);
+            }
+        }
+        if let Some(custom_data) = self.user_state.node_custom_data.get(&node_id).cloned() {
+            self.user_state
+                .node_custom_data
+                .insert(new_node, custom_data);
+        }
+
+        let offset = egui::vec2(20.0, 20.0) * self.state.pan_zoom.zoom;
+        let position = self.state.node_positions[node_id] + offset;
+        self.state.node_positions.insert(new_node, position);
+        self.state.node_order.push(new_node);
+
+        new_node
+    }
+
+    /// Sets the constant value of every unconnected input in the selected
+    /// nodes. Vector inputs get `value` in all of their components.
+    fn fill_unconnected_inputs(&mut self, value: f32) {
+        let graph = &mut self.state.graph;
+        for node_id in self.state.selected_nodes.iter().copied() {
+            for input in graph[node_id].input_ids().collect::<Vec<_>>() {
+                if graph.connection(input).is_some() {
+                    continue;
+                }
+                let param = &mut graph[input];
+                param.value = param.value.map_components(|_| value);
+            }
+        }
+    }
+
+    /// Returns the unconnected inputs, across the whole graph, whose constant
+    /// value matches `value`. Vector inputs match when any of their components
+    /// does. Boolean inputs never match, since most replacements would either
+    /// not change them or silently flip them.
+    fn inputs_matching(&self, value: f32) -> Vec<InputId> {
+        let graph = &self.state.graph;
+        let matches = |x: f32| (x - value).abs() <= REPLACE_EPSILON;
+        graph
+            .inputs
+            .iter()
+            .filter(|(input, param)| {
+                graph.connection(*input).is_none()
+                    && !matches!(param.value, MyValueType::Bool { .. })
+                    && param.value.components().into_iter().any(matches)
+            })
+            .map(|(input, _)| input)
+            .collect()
+    }
+
+    /// Replaces every unconnected constant matching `find` with `replace`. Only
+    /// the matching components of vector inputs are replaced.
+    fn replace_values(&mut self, find: f32, replace: f32) {
+        let replace_component = |x: f32| {
+            if (x - find).abs() <= REPLACE_EPSILON {
+                replace
+            } else {
+                x
+            }
+        };
+        for input in self.inputs_matching(find) {
+            let param = &mut self.state.graph[input];
+            param.value = param.value.map_components(replace_component);
+        }
+    }
+
+    /// Draws the inputs of the selected nodes. When several nodes of the same
+    /// kind are selected, editing an input sets it on all of them. Returns true
+    /// when any value was changed.
+    fn inspector_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let graph = &mut self.state.graph;
+        let selected = &self.state.selected_nodes;
+        let Some(&first) = selected.first() else {
+            ui.label("No node selected");
+            return false;
+        };
+        let template = graph[first].user_data.template;
+        if selected
+            .iter()
+            .any(|node_id| graph[*node_id].user_data.template != template)
+        {
+            ui.label("Multiple node types selected");
+            return false;
+        }
+
+        if selected.len() > 1 {
+            ui.label(format!("{} ({} nodes)", graph[first].label, selected.len()));
+        } else {
+            ui.label(&graph[first].label);
+        }
+        ui.separator();
+
+        let mut changed = false;
+        // Nodes built from the same template have their inputs in the same
+        // order, so inputs are matched by index.
+        for (index, (name, _)) in graph[first].inputs.clone().into_iter().enumerate() {
+            // Connected inputs get their value from the graph, so only the
+            // disconnected ones can be edited.
+            let inputs: Vec<InputId> = selected
+                .iter()
+                .map(|node_id| graph[*node_id].inputs[index].1)
+                .filter(|input| graph.connection(*input).is_none())
+                .collect();
+            let Some(&input) = inputs.first() else {
+                ui.horizontal(|ui| {
+                    ui.label(&name);
+                    ui.weak("connected");
+                });
+                continue;
+            };
+            let value = graph[input].value;
+            let mixed = inputs.iter().any(|input| graph[*input].value != value);
+
+            let mut new_value = value;
+            ui.horizontal(|ui| {
+                new_value.value_widget(
+                    &name,
+                    first,
+                    ui,
+                    &mut self.user_state,
+                    &graph[first].user_data,
+                );
+                if mixed {
+                    ui.weak("(mixed)")
+                        .on_hover_text("The selected nodes have different values");
+                }
+            });
+
+            if new_value != value {
+                // Only the edited components are applied, so that the other
+                // ones keep their (possibly mixed) values.
+                let old_components = value.components();
+                let new_components = new_value.components();
+                for input in inputs {
+                    let mut components = graph[input].value.components();
+                    for (i, (old, new)) in old_components.iter().zip(&new_components).enumerate() {
+                        if old != new {
+                            components[i] = *new;
+                        }
+                    }
+                    graph[input].value = graph[input].value.with_components(&components);
+                }
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
 impl eframe::App for NodeGraphExample {
     #[cfg(feature = "persistence")]
     /// If the persistence function is enabled,
     /// Called by the frame work to save state before shutdown.
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
         eframe::set_value(storage, PERSISTENCE_KEY, &self.state);
+        eframe::set_value(storage, USER_STATE_PERSISTENCE_KEY, &self.user_state);
+        eframe::set_value(storage, PREFS_PERSISTENCE_KEY, &self.prefs);
     }
     /// Called each time the UI needs repainting, which may be many times per second.
     /// Put your widgets into a `SidePanel`, `TopPanel`, `CentralPanel`, `Window` or `Area`.
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Evaluating the graph can be costly for big graphs, so users can opt
+        // out of evaluating the active node on every frame.
+        let mut should_evaluate = self.prefs.auto_evaluate;
         egui::TopBottomPanel::top("top").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
                 egui::widgets::global_dark_light_mode_switch(ui);
+                ui.menu_button("Edit", |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Value");
+                        ui.add(DragValue::new(&mut self.fill_value).speed(0.1));
+                    });
+                    let fill_button = egui::Button::new("Fill unconnected inputs");
+                    let has_selection = !self.state.selected_nodes.is_empty();
+                    if ui
+                        .add_enabled(has_selection, fill_button)
+                        .on_hover_text("Applies to the selected nodes")
+                        .clicked()
+                    {
+                        self.fill_unconnected_inputs(self.fill_value);
+                        should_evaluate = true;
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.button("Replace values...").clicked() {
+                        self.replace_dialog.get_or_insert_with(Default::default);
+                        ui.close_menu();
+                    }
+                });
+                ui.separator();
+                ui.checkbox(&mut self.prefs.focus_active_node, "Focus active node");
+                ui.checkbox(&mut self.prefs.auto_evaluate, "Auto evaluate");
+                let evaluate_button = egui::Button::new("Evaluate");
+                if ui
+                    .add_enabled(!self.prefs.auto_evaluate, evaluate_button)
+                    .clicked()
+                {
+                    should_evaluate = true;
+                }
             });
         });
+        self.user_state.focused_nodes = match self.user_state.active_node {
+            Some(node)
+                if self.prefs.focus_active_node && self.state.graph.nodes.contains_key(node) =>
+            {
+                Some(upstream_nodes(&self.state.graph, node))
+            }
+            _ => None,
+        };
+        egui::SidePanel::right("inspector").show(ctx, |ui| {
+            ui.heading("Inspector");
+            if self.inspector_ui(ui) {
+                should_evaluate = true;
+            }
+        });
         let graph_response = egui::CentralPanel::default()
             .show(ctx, |ui| {
                 self.state.draw_graph_editor(
@@ -426,35 +1676,128 @@ impl eframe::App for NodeGraphExample {
             })
             .inner;
         for node_response in graph_response.node_responses {
-            // Here, we ignore all other graph events. But you may find
+            // Here, we ignore most other graph events. But you may find
             // some use for them. For example, by playing a sound when a new
             // connection is created
-            if let NodeResponse::User(user_event) = node_response {
-                match user_event {
-                    MyResponse::SetActiveNode(node) => self.user_state.active_node = Some(node),
+            match node_response {
+                NodeResponse::User(user_event) => match user_event {
+                    MyResponse::SetActiveNode(node) => {
+                        self.user_state.active_node = Some(node);
+                        should_evaluate = true;
+                    }
                     MyResponse::ClearActiveNode => self.user_state.active_node = None,
+                    // The graph itself didn't change, but the result may have.
+                    MyResponse::CustomDataChanged => should_evaluate = true,
+                    MyResponse::Branch(node) => {
+                        let new_node = self.branch_node(node);
+                        self.state.selected_nodes = vec![new_node];
+                    }
+                },
+                // Custom node data is not owned by the graph, so it needs to be
+                // cleaned up when its node goes away.
+                NodeResponse::DeleteNodeFull { node_id, .. } => {
+                    self.user_state.node_custom_data.remove(&node_id);
                 }
+                _ => {}
             }
         }
 
+        if let Some(mut dialog) = self.replace_dialog {
+            let mut open = true;
+            egui::Window::new("Replace values")
+                .open(&mut open)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    egui::Grid::new("replace_values").show(ui, |ui| {
+                        ui.label("Find");
+                        ui.add(DragValue::new(&mut dialog.find).speed(0.1));
+                        ui.end_row();
+                        ui.label("Replace with");
+                        ui.add(DragValue::new(&mut dialog.replace).speed(0.1));
+                        ui.end_row();
+                    });
+                    ui.separator();
+
+                    let matches = self.inputs_matching(dialog.find);
+                    if matches.is_empty() {
+                        ui.weak("No matching inputs");
+                    }
+                    egui::ScrollArea::vertical()
+                        .max_height(200.0)
+                        .show(ui, |ui| {
+                            let graph = &self.state.graph;
+                            for input in &matches {
+                                let node = &graph[graph[*input].node];
+                                let name = node
+                                    .inputs
+                                    .iter()
+                                    .find(|(_, id)| id == input)
+                                    .map(|(name, _)| name.as_str())
+                                    .unwrap_or_default();
+                                ui.label(format!("{}: {}", node.label, name));
+                            }
+                        });
+
+                    let replace_button = egui::Button::new(format!("Replace {}", matches.len()));
+                    if ui
+                        .add_enabled(!matches.is_empty(), replace_button)
+                        .clicked()
+                    {
+                        self.replace_values(dialog.find, dialog.replace);
+                        should_evaluate = true;
+                    }
+                });
+            self.replace_dialog = if open { Some(dialog) } else { None };
+        }
+
         if let Some(node) = self.user_state.active_node {
             if self.state.graph.nodes.contains_key(node) {
-                let text = match evaluate_node(&self.state.graph, node, &mut HashMap::new()) {
-                    Ok(value) => format!("The result is: {:?}", value),
-                    Err(err) => format!("Execution error: {}", err),
-                };
-                ctx.debug_painter().text(
-                    egui::pos2(10.0, 35.0),
-                    egui::Align2::LEFT_TOP,
-                    text,
-                    TextStyle::Button.resolve(&ctx.style()),
-                    egui::Color32::WHITE,
-                );
+                if should_evaluate {
+                    let text = match evaluate_node(
+                        &self.state.graph,
+                        node,
+                        &self.user_state,
+                        &mut HashMap::new(),
+                    ) {
+                        Ok(value) => format!("The result is: {:?}", value),
+                        Err(err) => format!("Execution error: {}", err),
+                    };
+                    self.evaluation_result = Some(text);
+                }
+                if let Some(text) = &self.evaluation_result {
+                    ctx.debug_painter().text(
+                        egui::pos2(10.0, 35.0),
+                        egui::Align2::LEFT_TOP,
+                        text,
+                        TextStyle::Button.resolve(&ctx.style()),
+                        egui::Color32::WHITE,
+                    );
+                }
             } else {
                 self.user_state.active_node = None;
             }
         }
+        if self.user_state.active_node.is_none() {
+            self.evaluation_result = None;
+        }
+    }
+}
+
+/// Returns the given node, and all the nodes it (directly or indirectly)
+/// receives inputs from. These are all the nodes involved in its evaluation.
+pub fn upstream_nodes(graph: &MyGraph, node_id: NodeId) -> HashSet<NodeId> {
+    let mut visited = HashSet::new();
+    let mut pending = vec![node_id];
+    while let Some(node_id) = pending.pop() {
+        if visited.insert(node_id) {
+            for input in graph[node_id].input_ids() {
+                if let Some(output) = graph.connection(input) {
+                    pending.push(graph[output].node);
+                }
+            }
+        }
     }
+    visited
 }
 
 type OutputsCache = HashMap<OutputId, MyValueType>;
@@ -463,6 +1806,7 @@ type OutputsCache = HashMap<OutputId, MyValueType>;
 pub fn evaluate_node(
     graph: &MyGraph,
     node_id: NodeId,
+    user_state: &MyGraphState,
     outputs_cache: &mut OutputsCache,
 ) -> anyhow::Result<MyValueType> {
     // To solve a similar problem as creating node types above, we define an
@@ -472,13 +1816,20 @@ pub fn evaluate_node(
 
     struct Evaluator<'a> {
         graph: &'a MyGraph,
+        user_state: &'a MyGraphState,
         outputs_cache: &'a mut OutputsCache,
         node_id: NodeId,
     }
     impl<'a> Evaluator<'a> {
-        fn new(graph: &'a MyGraph, outputs_cache: &'a mut OutputsCache, node_id: NodeId) -> Self {
+        fn new(
+            graph: &'a MyGraph,
+            user_state: &'a MyGraphState,
+            outputs_cache: &'a mut OutputsCache,
+            node_id: NodeId,
+        ) -> Self {
             Self {
                 graph,
+                user_state,
                 outputs_cache,
                 node_id,
             }
@@ -486,7 +1837,16 @@ pub fn evaluate_node(
         fn evaluate_input(&mut self, name: &str) -> anyhow::Result<MyValueType> {
             // Calling `evaluate_input` recursively evaluates other nodes in the
             // graph until the input value for a paramater has been computed.
-            evaluate_input(self.graph, self.node_id, name, self.outputs_cache)
+            evaluate_input(
+                self.graph,
+                self.node_id,
+                name,
+                self.user_state,
+                self.outputs_cache,
+            )
+        }
+        fn custom_data(&self) -> Option<&'a MyNodeCustomData> {
+            self.user_state.node_custom_data.get(&self.node_id)
         }
         fn populate_output(
             &mut self,
@@ -520,10 +1880,25 @@ pub fn evaluate_node(
         fn output_scalar(&mut self, name: &str, value: f32) -> anyhow::Result<MyValueType> {
             self.populate_output(name, MyValueType::Scalar { value })
         }
+        fn input_bool(&mut self, name: &str) -> anyhow::Result<bool> {
+            self.evaluate_input(name)?.try_to_bool()
+        }
+        fn input_color(&mut self, name: &str) -> anyhow::Result<[f32; 3]> {
+            self.evaluate_input(name)?.try_to_color()
+        }
+        fn output_color(&mut self, name: &str, value: [f32; 3]) -> anyhow::Result<MyValueType> {
+            self.populate_output(name, MyValueType::Color { value })
+        }
+        fn input_vec4(&mut self, name: &str) -> anyhow::Result<[f32; 4]> {
+            self.evaluate_input(name)?.try_to_vec4()
+        }
+        fn output_vec4(&mut self, name: &str, value: [f32; 4]) -> anyhow::Result<MyValueType> {
+            self.populate_output(name, MyValueType::Vec4 { value })
+        }
     }
 
     let node = &graph[node_id];
-    let mut evaluator = Evaluator::new(graph, outputs_cache, node_id);
+    let mut evaluator = Evaluator::new(graph, user_state, outputs_cache, node_id);
     match node.user_data.template {
         MyNodeTemplate::AddScalar => {
             let a = evaluator.input_scalar("A")?;
@@ -559,9 +1934,501 @@ pub fn evaluate_node(
             let value = evaluator.input_scalar("value")?;
             evaluator.output_scalar("out", value)
         }
+        MyNodeTemplate::CurveRemap => {
+            let x = evaluator.input_scalar("x")?;
+            let value = match evaluator.custom_data() {
+                Some(MyNodeCustomData::Curve(curve)) => curve.sample(x),
+                _ => Curve::default().sample(x),
+            };
+            evaluator.output_scalar("out", value)
+        }
+        MyNodeTemplate::PolarCoordinates => {
+            let uv = evaluator.input_vector("uv")?;
+            let center = evaluator.input_vector("center")?;
+            let radial_scale = evaluator.input_scalar("radial scale")?;
+            let length_scale = evaluator.input_scalar("length scale")?;
+            // The angle around the center is mapped to x, in the [0, 1] range,
+            // and the distance to the center is mapped to y.
+            let delta = uv - center;
+            let angle = delta.y.atan2(delta.x) / std::f32::consts::TAU + 0.5;
+            let radius = delta.length();
+            evaluator.output_vector(
+                "out",
+                egui::vec2(angle * length_scale, radius * radial_scale),
+            )
+        }
+        MyNodeTemplate::GammaCorrect => {
+            let x = evaluator.input_scalar("x")?;
+            let gamma = evaluator.input_scalar("gamma")?;
+            // A gamma of zero would divide by zero, so it is kept slightly positive.
+            let gamma = gamma.max(1e-4);
+            evaluator.output_scalar("out", x.max(0.0).powf(gamma.recip()))
+        }
+        MyNodeTemplate::TimeWrap => {
+            // Keeps ever-growing values, like time, bounded before feeding them
+            // to periodic functions where precision would otherwise suffer.
+            let t = evaluator.input_scalar("t")?;
+            let period = evaluator.input_scalar("period")?;
+            let value = if period != 0.0 { t % period } else { t };
+            evaluator.output_scalar("out", value)
+        }
+        MyNodeTemplate::SoftClamp => {
+            let x = evaluator.input_scalar("x")?;
+            let knee = evaluator.input_scalar("knee")?;
+            // Values below `1 - knee` are left untouched. Above that, the curve
+            // smoothly rolls off towards 1 without ever reaching it.
+            let threshold = 1.0 - knee;
+            let value = if knee <= 0.0 {
+                x.min(1.0)
+            } else if x <= threshold {
+                x
+            } else {
+                threshold + knee * (1.0 - (-(x - threshold) / knee).exp())
+            };
+            evaluator.output_scalar("out", value)
+        }
+        MyNodeTemplate::Caustics => {
+            let uv = evaluator.input_vector("uv")?;
+            let time = evaluator.input_scalar("time")?;
+            let scale = evaluator.input_scalar("scale")?;
+            // Two layers of a warped sine lattice, moving in opposite directions.
+            // Where both layers are bright at the same time, we get the thin
+            // bright lines characteristic of caustics.
+            let layer = |p: egui::Vec2, t: f32| {
+                let warped = p + egui::vec2((p.y + t).sin(), (p.x - t).cos());
+                let v = (warped.x.sin() * warped.y.sin()).abs();
+                (1.0 - v).powi(4)
+            };
+            let p = uv * scale * std::f32::consts::TAU;
+            let first = layer(p, time);
+            let second = layer(p * 1.3 + egui::vec2(1.7, 9.2), -0.8 * time);
+            evaluator.output_scalar("out", first.min(second))
+        }
+        MyNodeTemplate::Clamp => {
+            let x = evaluator.input_scalar("x")?;
+            let min = evaluator.input_scalar("min")?;
+            let max = evaluator.input_scalar("max")?;
+            // Unlike `f32::clamp`, this doesn't panic when the edges are swapped.
+            evaluator.output_scalar("out", x.max(min).min(max))
+        }
+        MyNodeTemplate::ClampVector => {
+            let v = evaluator.input_vector("v")?;
+            let min = evaluator.input_vector("min")?;
+            let max = evaluator.input_vector("max")?;
+            evaluator.output_vector("out", v.max(min).min(max))
+        }
+        MyNodeTemplate::Abs => {
+            let x = evaluator.input_scalar("x")?;
+            evaluator.output_scalar("out", x.abs())
+        }
+        MyNodeTemplate::AbsVector => {
+            let v = evaluator.input_vector("v")?;
+            evaluator.output_vector("out", v.abs())
+        }
+        MyNodeTemplate::Floor => {
+            let x = evaluator.input_scalar("x")?;
+            evaluator.output_scalar("out", x.floor())
+        }
+        MyNodeTemplate::Ceil => {
+            let x = evaluator.input_scalar("x")?;
+            evaluator.output_scalar("out", x.ceil())
+        }
+        MyNodeTemplate::Round => {
+            let x = evaluator.input_scalar("x")?;
+            evaluator.output_scalar("out", x.round())
+        }
+        MyNodeTemplate::FloorVector => {
+            let v = evaluator.input_vector("v")?;
+            evaluator.output_vector("out", v.floor())
+        }
+        MyNodeTemplate::CeilVector => {
+            let v = evaluator.input_vector("v")?;
+            evaluator.output_vector("out", v.ceil())
+        }
+        MyNodeTemplate::RoundVector => {
+            let v = evaluator.input_vector("v")?;
+            evaluator.output_vector("out", v.round())
+        }
+        MyNodeTemplate::Frac => {
+            let x = evaluator.input_scalar("x")?;
+            // Unlike `f32::fract`, the result is always in [0, 1), even for negative x.
+            evaluator.output_scalar("out", x - x.floor())
+        }
+        MyNodeTemplate::FracVector => {
+            let v = evaluator.input_vector("v")?;
+            evaluator.output_vector("out", v - v.floor())
+        }
+        MyNodeTemplate::WrapUv => {
+            let uv = evaluator.input_vector("uv")?;
+            let mode = match evaluator.custom_data() {
+                Some(MyNodeCustomData::WrapMode(mode)) => *mode,
+                _ => WrapMode::Repeat,
+            };
+            evaluator.output_vector("out", egui::vec2(mode.apply(uv.x), mode.apply(uv.y)))
+        }
+        MyNodeTemplate::Mod => {
+            let a = evaluator.input_scalar("a")?;
+            let b = evaluator.input_scalar("b")?;
+            // Same as `fmod`: the result has the sign of `a`.
+            evaluator.output_scalar("out", a % b)
+        }
+        MyNodeTemplate::Exp => {
+            let x = evaluator.input_scalar("x")?;
+            evaluator.output_scalar("out", x.exp())
+        }
+        MyNodeTemplate::Log => {
+            let x = evaluator.input_scalar("x")?;
+            evaluator.output_scalar("out", x.ln())
+        }
+        MyNodeTemplate::Log2 => {
+            let x = evaluator.input_scalar("x")?;
+            evaluator.output_scalar("out", x.log2())
+        }
+        MyNodeTemplate::MathConstant => {
+            let value = match evaluator.custom_data() {
+                Some(MyNodeCustomData::MathConstant(constant)) => constant.value(),
+                _ => MathConstant::Pi.value(),
+            };
+            evaluator.output_scalar("out", value)
+        }
+        MyNodeTemplate::Pulse => {
+            let x = evaluator.input_scalar("x")?;
+            let center = evaluator.input_scalar("center")?;
+            let width = evaluator.input_scalar("width")?;
+            // A smooth bump that is 1 at the center and falls off to 0 at a distance
+            // of `width` on either side.
+            let width = width.abs().max(1e-4);
+            let t = (1.0 - (x - center).abs() / width).clamp(0.0, 1.0);
+            evaluator.output_scalar("out", t * t * (3.0 - 2.0 * t))
+        }
+        MyNodeTemplate::Tan => {
+            let x = evaluator.input_scalar("x")?;
+            evaluator.output_scalar("out", x.tan())
+        }
+        MyNodeTemplate::Asin => {
+            let x = evaluator.input_scalar("x")?;
+            evaluator.output_scalar("out", x.asin())
+        }
+        MyNodeTemplate::Acos => {
+            let x = evaluator.input_scalar("x")?;
+            evaluator.output_scalar("out", x.acos())
+        }
+        MyNodeTemplate::Atan => {
+            let x = evaluator.input_scalar("x")?;
+            evaluator.output_scalar("out", x.atan())
+        }
+        MyNodeTemplate::Atan2 => {
+            let y = evaluator.input_scalar("y")?;
+            let x = evaluator.input_scalar("x")?;
+            evaluator.output_scalar("out", y.atan2(x))
+        }
+        MyNodeTemplate::Length => {
+            let v = evaluator.input_vector("v")?;
+            evaluator.output_scalar("out", v.length())
+        }
+        MyNodeTemplate::Distance => {
+            let a = evaluator.input_vector("a")?;
+            let b = evaluator.input_vector("b")?;
+            evaluator.output_scalar("out", (a - b).length())
+        }
+        MyNodeTemplate::Rsqrt => {
+            let x = evaluator.input_scalar("x")?;
+            evaluator.output_scalar("out", x.sqrt().recip())
+        }
+        MyNodeTemplate::OneMinus => {
+            let x = evaluator.input_scalar("x")?;
+            evaluator.output_scalar("out", 1.0 - x)
+        }
+        MyNodeTemplate::OneMinusVector => {
+            let v = evaluator.input_vector("v")?;
+            evaluator.output_vector("out", egui::Vec2::splat(1.0) - v)
+        }
+        MyNodeTemplate::Remap => {
+            let x = evaluator.input_scalar("x")?;
+            let in_min = evaluator.input_scalar("in min")?;
+            let in_max = evaluator.input_scalar("in max")?;
+            let out_min = evaluator.input_scalar("out min")?;
+            let out_max = evaluator.input_scalar("out max")?;
+            // An empty input range would divide by zero, so it maps everything to
+            // the start of the output range instead.
+            let t = if in_max != in_min {
+                (x - in_min) / (in_max - in_min)
+            } else {
+                0.0
+            };
+            evaluator.output_scalar("out", out_min + t * (out_max - out_min))
+        }
+        MyNodeTemplate::ThreeStopGradient => {
+            let t = evaluator.input_scalar("t")?;
+            let a = evaluator.input_color("a")?;
+            let b = evaluator.input_color("b")?;
+            let c = evaluator.input_color("c")?;
+            // The first half of the [0, 1] range blends from `a` to `b`, and the
+            // second half from `b` to `c`.
+            let lerp =
+                |a: [f32; 3], b: [f32; 3], t: f32| [0, 1, 2].map(|i| a[i] + (b[i] - a[i]) * t);
+            let t = t.clamp(0.0, 1.0);
+            let value = if t < 0.5 {
+                lerp(a, b, t * 2.0)
+            } else {
+                lerp(b, c, t * 2.0 - 1.0)
+            };
+            evaluator.output_color("out", value)
+        }
+        MyNodeTemplate::Posterize => {
+            let x = evaluator.input_scalar("x")?;
+            let steps = evaluator.input_scalar("steps")?;
+            // Zero steps would divide by zero, so the value is left untouched.
+            let value = if steps != 0.0 {
+                (x * steps).floor() / steps
+            } else {
+                x
+            };
+            evaluator.output_scalar("out", value)
+        }
+        MyNodeTemplate::PosterizeVector => {
+            let v = evaluator.input_vector("v")?;
+            let steps = evaluator.input_scalar("steps")?;
+            let value = if steps != 0.0 {
+                (v * steps).floor() / steps
+            } else {
+                v
+            };
+            evaluator.output_vector("out", value)
+        }
+        MyNodeTemplate::FlowDistort => {
+            let uv = evaluator.input_vector("uv")?;
+            let flow = evaluator.input_vector("flow")?;
+            let strength = evaluator.input_scalar("strength")?;
+            let time = evaluator.input_scalar("time")?;
+            // The flow is typically read from a texture, where directions are stored
+            // in the [0, 1] range, so it is remapped to [-1, 1] first.
+            let direction = flow * 2.0 - egui::Vec2::splat(1.0);
+            evaluator.output_vector("out", uv + direction * strength * time)
+        }
+        MyNodeTemplate::Panner => {
+            let uv = evaluator.input_vector("uv")?;
+            let speed = evaluator.input_vector("speed")?;
+            let time = evaluator.input_scalar("time")?;
+            evaluator.output_vector("out", uv + speed * time)
+        }
+        MyNodeTemplate::UvDebug => {
+            let uv = evaluator.input_vector("uv")?;
+            // The UVs themselves make the red and green gradients. A subtle grid is
+            // drawn on top, with ten cells per unit, so tiling and stretching are
+            // easy to spot.
+            let cells = 10.0;
+            let line_width = 0.05;
+            let line = |x: f32| {
+                let distance = (x * cells - (x * cells).round()).abs();
+                1.0 - (distance / line_width).clamp(0.0, 1.0)
+            };
+            let grid = line(uv.x).max(line(uv.y));
+            let gradient = uv - uv.floor();
+            evaluator.output_vector("out", gradient * (1.0 - 0.25 * grid))
+        }
+        MyNodeTemplate::MakeVec4 => {
+            let r = evaluator.input_scalar("r")?;
+            let g = evaluator.input_scalar("g")?;
+            let b = evaluator.input_scalar("b")?;
+            let a = evaluator.input_scalar("a")?;
+            evaluator.output_vec4("out", [r, g, b, a])
+        }
+        MyNodeTemplate::CombineAlpha => {
+            let a = evaluator.input_scalar("a")?;
+            let b = evaluator.input_scalar("b")?;
+            let mode = match evaluator.custom_data() {
+                Some(MyNodeCustomData::AlphaMode(mode)) => *mode,
+                _ => AlphaMode::Multiply,
+            };
+            evaluator.output_scalar("out", mode.apply(a, b))
+        }
+        MyNodeTemplate::MakeColor => {
+            let color = evaluator.input_color("color")?;
+            evaluator.output_color("out", color)
+        }
+        MyNodeTemplate::SignedPow => {
+            let x = evaluator.input_scalar("x")?;
+            let exp = evaluator.input_scalar("exp")?;
+            // The curve is mirrored around zero, so negative inputs don't produce
+            // NaN like they would with a plain `powf`.
+            let value = if x == 0.0 {
+                0.0
+            } else {
+                x.signum() * x.abs().powf(exp)
+            };
+            evaluator.output_scalar("out", value)
+        }
+        MyNodeTemplate::RadialGradient => {
+            let uv = evaluator.input_vector("uv")?;
+            let center = evaluator.input_vector("center")?;
+            let radius = evaluator.input_scalar("radius")?;
+            let softness = evaluator.input_scalar("softness")?;
+            // 1 inside the circle, smoothly falling off to 0 over the last `softness`
+            // units before the radius. A softness of zero gives a hard edge.
+            let distance = (uv - center).length();
+            let value = if softness > 0.0 {
+                let t = ((radius - distance) / softness).clamp(0.0, 1.0);
+                t * t * (3.0 - 2.0 * t)
+            } else if distance <= radius {
+                1.0
+            } else {
+                0.0
+            };
+            evaluator.output_scalar("out", value)
+        }
+        MyNodeTemplate::Select => {
+            let condition = evaluator.input_bool("condition")?;
+            let if_true = evaluator.input_scalar("if true")?;
+            let if_false = evaluator.input_scalar("if false")?;
+            evaluator.output_scalar("out", if condition { if_true } else { if_false })
+        }
+        MyNodeTemplate::ToonRamp => {
+            let ndl = evaluator.input_scalar("ndl")?;
+            let threshold_1 = evaluator.input_scalar("threshold 1")?;
+            let threshold_2 = evaluator.input_scalar("threshold 2")?;
+            let shadow = evaluator.input_color("shadow")?;
+            let mid = evaluator.input_color("mid")?;
+            let light = evaluator.input_color("light")?;
+            // Hard bands, as is typical for toon shading.
+            let color = if ndl < threshold_1 {
+                shadow
+            } else if ndl < threshold_2 {
+                mid
+            } else {
+                light
+            };
+            evaluator.output_color("out", color)
+        }
+        MyNodeTemplate::Refract => {
+            let i = evaluator.input_vector("I")?;
+            let n = evaluator.input_vector("N")?;
+            let eta = evaluator.input_scalar("eta")?;
+            // Same as HLSL's `refract`. On total internal reflection, the result is
+            // the zero vector.
+            let cos_i = n.dot(i);
+            let k = 1.0 - eta * eta * (1.0 - cos_i * cos_i);
+            let value = if k < 0.0 {
+                egui::Vec2::ZERO
+            } else {
+                eta * i - (eta * cos_i + k.sqrt()) * n
+            };
+            evaluator.output_vector("out", value)
+        }
+        MyNodeTemplate::UnpackNormal => {
+            // Normal maps store directions in the [0, 1] range of a texture. Two
+            // channel normal maps only store x and y, which is all a 2d vector holds.
+            let packed = evaluator.input_vector("packed")?;
+            evaluator.output_vector("out", packed * 2.0 - egui::Vec2::splat(1.0))
+        }
+        MyNodeTemplate::Luminance => {
+            let [r, g, b] = evaluator.input_color("color")?;
+            // Rec. 601 luma weights.
+            evaluator.output_scalar("out", 0.299 * r + 0.587 * g + 0.114 * b)
+        }
+        MyNodeTemplate::Flicker => {
+            let time = evaluator.input_scalar("time")?;
+            let frequency = evaluator.input_scalar("frequency")?;
+            let seed = evaluator.input_scalar("seed")?;
+            // A new random value is picked `frequency` times per unit of time. The
+            // output is off for the lowest 30% of those, so it's on most of the time,
+            // like a failing neon light.
+            let value = hash((time * frequency).floor() + seed * 7.31);
+            evaluator.output_scalar("out", if value < 0.3 { 0.0 } else { 1.0 })
+        }
+        MyNodeTemplate::Contrast => {
+            let color = evaluator.input_color("color")?;
+            let amount = evaluator.input_scalar("amount")?;
+            // Scales the distance of each channel to mid-gray.
+            evaluator.output_color("out", color.map(|c| (c - 0.5) * amount + 0.5))
+        }
+        MyNodeTemplate::Bilerp => {
+            let bottom_left = evaluator.input_color("bottom left")?;
+            let bottom_right = evaluator.input_color("bottom right")?;
+            let top_left = evaluator.input_color("top left")?;
+            let top_right = evaluator.input_color("top right")?;
+            let u = evaluator.input_scalar("u")?;
+            let v = evaluator.input_scalar("v")?;
+            let lerp =
+                |a: [f32; 3], b: [f32; 3], t: f32| [0, 1, 2].map(|i| a[i] + (b[i] - a[i]) * t);
+            let bottom = lerp(bottom_left, bottom_right, u);
+            let top = lerp(top_left, top_right, u);
+            evaluator.output_color("out", lerp(bottom, top, v))
+        }
+        MyNodeTemplate::Blend => {
+            let a = evaluator.input_color("a")?;
+            let b = evaluator.input_color("b")?;
+            let t = evaluator.input_scalar("t")?;
+            let mode = match evaluator.custom_data() {
+                Some(MyNodeCustomData::BlendMode(mode)) => *mode,
+                _ => BlendMode::Multiply,
+            };
+            // `t` fades between the base color and the blended one.
+            let color = [0, 1, 2].map(|i| {
+                let blended = mode.apply(a[i], b[i]);
+                a[i] + (blended - a[i]) * t
+            });
+            evaluator.output_color("out", color)
+        }
+        MyNodeTemplate::RoughnessToAlpha => {
+            let roughness = evaluator.input_scalar("roughness")?;
+            // Squaring the perceptual roughness gives the alpha used by most
+            // microfacet distributions.
+            evaluator.output_scalar("out", roughness * roughness)
+        }
+        MyNodeTemplate::FrameTime => {
+            let time = evaluator.input_scalar("time")?;
+            let fps = evaluator.input_scalar("fps")?;
+            // Snaps the time to the start of its frame. Zero fps would divide by zero,
+            // so the time is left untouched.
+            let value = if fps != 0.0 {
+                (time * fps).floor() / fps
+            } else {
+                time
+            };
+            evaluator.output_scalar("out", value)
+        }
+        MyNodeTemplate::FrameCount => {
+            let time = evaluator.input_scalar("time")?;
+            let fps = evaluator.input_scalar("fps")?;
+            evaluator.output_scalar("out", (time * fps).floor())
+        }
+        MyNodeTemplate::RandomSeed => {
+            let time = evaluator.input_scalar("time")?;
+            // There is no way to tell objects apart here, so the seed only varies
+            // over time. Per-object seeds need to come from outside the graph.
+            evaluator.output_scalar("out", hash(time))
+        }
+        MyNodeTemplate::HueShift => {
+            let color = evaluator.input_color("color")?;
+            let time = evaluator.input_scalar("time")?;
+            let speed = evaluator.input_scalar("speed")?;
+            // The hue goes around the color wheel `speed` times per unit of time.
+            let mut hsva = egui::ecolor::Hsva::from_rgb(color);
+            hsva.h = (hsva.h + time * speed).rem_euclid(1.0);
+            evaluator.output_color("out", hsva.to_rgb())
+        }
+        MyNodeTemplate::Premultiply => {
+            let [r, g, b, a] = evaluator.input_vec4("color")?;
+            evaluator.output_vec4("out", [r * a, g * a, b * a, a])
+        }
+        MyNodeTemplate::Unpremultiply => {
+            let [r, g, b, a] = evaluator.input_vec4("color")?;
+            // A fully transparent color has no color left to recover, and dividing by
+            // zero would give NaN.
+            let divisor = a.max(1e-5);
+            evaluator.output_vec4("out", [r / divisor, g / divisor, b / divisor, a])
+        }
     }
 }
 
+/// Hashes `x` into a pseudo-random value in [0, 1). The same input always
+/// gives the same value, but nearby inputs give unrelated ones.
+fn hash(x: f32) -> f32 {
+    let h = (x * 12.9898).sin() * 43758.547;
+    h - h.floor()
+}
+
 fn populate_output(
     graph: &MyGraph,
     outputs_cache: &mut OutputsCache,
@@ -579,27 +2446,36 @@ fn evaluate_input(
     graph: &MyGraph,
     node_id: NodeId,
     param_name: &str,
+    user_state: &MyGraphState,
     outputs_cache: &mut OutputsCache,
 ) -> anyhow::Result<MyValueType> {
     let input_id = graph[node_id].get_input(param_name)?;
 
-    // The output of another node is connected.
+    // The output of another node is connected. Its type may differ from the
+    // type of the input, in which case the value is converted.
     if let Some(other_output_id) = graph.connection(input_id) {
+        let typ = &graph[input_id].typ;
         // The value was already computed due to the evaluation of some other
         // node. We simply return value from the cache.
         if let Some(other_value) = outputs_cache.get(&other_output_id) {
-            Ok(*other_value)
+            Ok(other_value.convert_to(typ))
         }
         // This is the first time encountering this node, so we need to
         // recursively evaluate it.
         else {
             // Calling this will populate the cache
-            evaluate_node(graph, graph[other_output_id].node, outputs_cache)?;
+            evaluate_node(
+                graph,
+                graph[other_output_id].node,
+                user_state,
+                outputs_cache,
+            )?;
 
             // Now that we know the value is cached, return it
-            Ok(*outputs_cache
+            Ok(outputs_cache
                 .get(&other_output_id)
-                .expect("Cache should be populated"))
+                .expect("Cache should be populated")
+                .convert_to(typ))
         }
     }
     // No existing connection, take the inline value instead.
@@ -607,3 +2483,277 @@ fn evaluate_input(
         Ok(graph[input_id].value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add_node(
+        graph: &mut MyGraph,
+        user_state: &mut MyGraphState,
+        template: MyNodeTemplate,
+    ) -> NodeId {
+        graph.add_node(
+            template.node_graph_label(user_state),
+            template.user_data(user_state),
+            |graph, node_id| template.build_node(graph, user_state, node_id),
+        )
+    }
+
+    fn set_scalar(graph: &mut MyGraph, node_id: NodeId, name: &str, value: f32) {
+        let input = graph[node_id].get_input(name).unwrap();
+        graph[input].value = MyValueType::Scalar { value };
+    }
+
+    fn set_vector(graph: &mut MyGraph, node_id: NodeId, name: &str, value: egui::Vec2) {
+        let input = graph[node_id].get_input(name).unwrap();
+        graph[input].value = MyValueType::Vec2 { value };
+    }
+
+    fn connect(graph: &mut MyGraph, from: NodeId, output: &str, to: NodeId, input: &str) {
+        let output = graph[from].get_output(output).unwrap();
+        let input = graph[to].get_input(input).unwrap();
+        graph.add_connection(output, input);
+    }
+
+    fn evaluate_scalar(graph: &MyGraph, user_state: &MyGraphState, node_id: NodeId) -> f32 {
+        evaluate_node(graph, node_id, user_state, &mut HashMap::new())
+            .unwrap()
+            .try_to_scalar()
+            .unwrap()
+    }
+
+    fn evaluate_vector(graph: &MyGraph, user_state: &MyGraphState, node_id: NodeId) -> egui::Vec2 {
+        evaluate_node(graph, node_id, user_state, &mut HashMap::new())
+            .unwrap()
+            .try_to_vec2()
+            .unwrap()
+    }
+
+    #[test]
+    pub fn test_exponential_nodes() {
+        let mut graph = MyGraph::new();
+        let mut user_state = MyGraphState::default();
+
+        let cases = [
+            (MyNodeTemplate::Exp, 1.0, std::f32::consts::E),
+            (MyNodeTemplate::Log, std::f32::consts::E, 1.0),
+            (MyNodeTemplate::Log2, 8.0, 3.0),
+        ];
+        for (template, x, expected) in cases {
+            let node = add_node(&mut graph, &mut user_state, template);
+            set_scalar(&mut graph, node, "x", x);
+            let result = evaluate_scalar(&graph, &user_state, node);
+            assert!((result - expected).abs() < 1e-5, "{result} != {expected}");
+        }
+
+        // Exp undoes Log when chained.
+        let log = add_node(&mut graph, &mut user_state, MyNodeTemplate::Log);
+        let exp = add_node(&mut graph, &mut user_state, MyNodeTemplate::Exp);
+        set_scalar(&mut graph, log, "x", 5.0);
+        connect(&mut graph, log, "out", exp, "x");
+        assert!((evaluate_scalar(&graph, &user_state, exp) - 5.0).abs() < 1e-5);
+    }
+
+    #[test]
+    pub fn test_vector_length_nodes() {
+        let mut graph = MyGraph::new();
+        let mut user_state = MyGraphState::default();
+
+        let length = add_node(&mut graph, &mut user_state, MyNodeTemplate::Length);
+        set_vector(&mut graph, length, "v", egui::vec2(3.0, 4.0));
+        assert_eq!(evaluate_scalar(&graph, &user_state, length), 5.0);
+
+        let distance = add_node(&mut graph, &mut user_state, MyNodeTemplate::Distance);
+        set_vector(&mut graph, distance, "a", egui::vec2(1.0, 1.0));
+        set_vector(&mut graph, distance, "b", egui::vec2(4.0, -3.0));
+        assert_eq!(evaluate_scalar(&graph, &user_state, distance), 5.0);
+
+        // The scalar outputs can feed regular scalar nodes.
+        let add = add_node(&mut graph, &mut user_state, MyNodeTemplate::AddScalar);
+        connect(&mut graph, length, "out", add, "A");
+        connect(&mut graph, distance, "out", add, "B");
+        assert_eq!(evaluate_scalar(&graph, &user_state, add), 10.0);
+    }
+
+    #[test]
+    pub fn test_remap_node() {
+        let mut graph = MyGraph::new();
+        let mut user_state = MyGraphState::default();
+
+        // Only the first and last inputs are connected, the ones in between
+        // keep their defaults, mapping [0, 1] to [0, out max].
+        let x = add_node(&mut graph, &mut user_state, MyNodeTemplate::MakeScalar);
+        let out_max = add_node(&mut graph, &mut user_state, MyNodeTemplate::MakeScalar);
+        let remap = add_node(&mut graph, &mut user_state, MyNodeTemplate::Remap);
+        set_scalar(&mut graph, x, "value", 0.25);
+        set_scalar(&mut graph, out_max, "value", 8.0);
+        connect(&mut graph, x, "out", remap, "x");
+        connect(&mut graph, out_max, "out", remap, "out max");
+        assert_eq!(evaluate_scalar(&graph, &user_state, remap), 2.0);
+
+        // Mapping [-1, 1] to [0, out max] instead.
+        set_scalar(&mut graph, remap, "in min", -1.0);
+        assert_eq!(evaluate_scalar(&graph, &user_state, remap), 5.0);
+
+        // An empty input range doesn't produce NaN.
+        set_scalar(&mut graph, remap, "in max", -1.0);
+        assert_eq!(evaluate_scalar(&graph, &user_state, remap), 0.0);
+    }
+
+    #[test]
+    pub fn test_panner_node() {
+        let mut graph = MyGraph::new();
+        let mut user_state = MyGraphState::default();
+
+        let panner = add_node(&mut graph, &mut user_state, MyNodeTemplate::Panner);
+        set_vector(&mut graph, panner, "uv", egui::vec2(0.5, 0.5));
+
+        // While time is unconnected, the UVs don't move.
+        let uv = evaluate_vector(&graph, &user_state, panner);
+        assert_eq!(uv, egui::vec2(0.5, 0.5));
+
+        let time = add_node(&mut graph, &mut user_state, MyNodeTemplate::MakeScalar);
+        set_scalar(&mut graph, time, "value", 2.0);
+        connect(&mut graph, time, "out", panner, "time");
+        let uv = evaluate_vector(&graph, &user_state, panner);
+        assert!((uv - egui::vec2(0.7, 0.5)).length() < 1e-5, "{uv:?}");
+    }
+
+    #[test]
+    pub fn test_refract_node() {
+        let mut graph = MyGraph::new();
+        let mut user_state = MyGraphState::default();
+
+        let incident = add_node(&mut graph, &mut user_state, MyNodeTemplate::MakeVector);
+        let normal = add_node(&mut graph, &mut user_state, MyNodeTemplate::MakeVector);
+        let refract = add_node(&mut graph, &mut user_state, MyNodeTemplate::Refract);
+        connect(&mut graph, incident, "out", refract, "I");
+        connect(&mut graph, normal, "out", refract, "N");
+
+        // A ray hitting a horizontal surface at 45 degrees.
+        let d = std::f32::consts::FRAC_1_SQRT_2;
+        set_scalar(&mut graph, incident, "x", d);
+        set_scalar(&mut graph, incident, "y", -d);
+        set_scalar(&mut graph, normal, "y", 1.0);
+
+        // With an eta of one, the ray goes straight through.
+        let out = evaluate_vector(&graph, &user_state, refract);
+        assert!((out - egui::vec2(d, -d)).length() < 1e-5, "{out:?}");
+
+        // Entering a denser medium bends the ray towards the normal, following
+        // Snell's law.
+        set_scalar(&mut graph, refract, "eta", 0.5);
+        let out = evaluate_vector(&graph, &user_state, refract);
+        assert!((out.length() - 1.0).abs() < 1e-5, "{out:?}");
+        assert!((out.x - 0.5 * d).abs() < 1e-5, "{out:?}");
+
+        // Leaving it at a grazing angle causes total internal reflection.
+        set_scalar(&mut graph, refract, "eta", 2.0);
+        let out = evaluate_vector(&graph, &user_state, refract);
+        assert_eq!(out, egui::Vec2::ZERO);
+    }
+
+    #[test]
+    pub fn test_unpack_normal_node() {
+        let mut graph = MyGraph::new();
+        let mut user_state = MyGraphState::default();
+
+        // The default packed value is a flat normal.
+        let unpack = add_node(&mut graph, &mut user_state, MyNodeTemplate::UnpackNormal);
+        assert_eq!(
+            evaluate_vector(&graph, &user_state, unpack),
+            egui::Vec2::ZERO
+        );
+
+        let packed = add_node(&mut graph, &mut user_state, MyNodeTemplate::MakeVector);
+        set_scalar(&mut graph, packed, "x", 1.0);
+        set_scalar(&mut graph, packed, "y", 0.25);
+        connect(&mut graph, packed, "out", unpack, "packed");
+        let normal = evaluate_vector(&graph, &user_state, unpack);
+        assert_eq!(normal, egui::vec2(1.0, -0.5));
+    }
+
+    #[test]
+    pub fn test_contrast_node() {
+        let mut graph = MyGraph::new();
+        let mut user_state = MyGraphState::default();
+
+        // The default amount leaves the color untouched.
+        let contrast = add_node(&mut graph, &mut user_state, MyNodeTemplate::Contrast);
+        let color = [0.25, 0.5, 1.0];
+        let input = graph[contrast].get_input("color").unwrap();
+        graph[input].value = MyValueType::Color { value: color };
+        let value = evaluate_node(&graph, contrast, &user_state, &mut HashMap::new()).unwrap();
+        assert_eq!(value.try_to_color().unwrap(), color);
+    }
+
+    #[test]
+    pub fn test_implicit_conversions() {
+        let mut graph = MyGraph::new();
+        let mut user_state = MyGraphState::default();
+
+        // Scalars are broadcast to all the components of a vector.
+        let scalar = add_node(&mut graph, &mut user_state, MyNodeTemplate::MakeScalar);
+        set_scalar(&mut graph, scalar, "value", 2.0);
+        let add_vector = add_node(&mut graph, &mut user_state, MyNodeTemplate::AddVector);
+        connect(&mut graph, scalar, "out", add_vector, "v1");
+        assert_eq!(
+            evaluate_vector(&graph, &user_state, add_vector),
+            egui::vec2(2.0, 2.0)
+        );
+
+        // Vectors become a scalar by keeping their first component.
+        let vector = add_node(&mut graph, &mut user_state, MyNodeTemplate::MakeVector);
+        set_scalar(&mut graph, vector, "x", 3.0);
+        set_scalar(&mut graph, vector, "y", 4.0);
+        let add_scalar = add_node(&mut graph, &mut user_state, MyNodeTemplate::AddScalar);
+        connect(&mut graph, vector, "out", add_scalar, "A");
+        assert_eq!(evaluate_scalar(&graph, &user_state, add_scalar), 3.0);
+
+        // Any nonzero number is true.
+        let select = add_node(&mut graph, &mut user_state, MyNodeTemplate::Select);
+        connect(&mut graph, scalar, "out", select, "condition");
+        assert_eq!(evaluate_scalar(&graph, &user_state, select), 1.0);
+        set_scalar(&mut graph, scalar, "value", 0.0);
+        assert_eq!(evaluate_scalar(&graph, &user_state, select), 0.0);
+
+        // Missing components are filled with zero, and one for alpha. An
+        // alpha of one leaves the color untouched when premultiplying.
+        let premultiply = add_node(&mut graph, &mut user_state, MyNodeTemplate::Premultiply);
+        connect(&mut graph, vector, "out", premultiply, "color");
+        let value = evaluate_node(&graph, premultiply, &user_state, &mut HashMap::new()).unwrap();
+        assert_eq!(value.try_to_vec4().unwrap(), [3.0, 4.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    pub fn test_replace_values_skips_bools() {
+        let mut app = NodeGraphExample::default();
+        let select = add_node(
+            &mut app.state.graph,
+            &mut app.user_state,
+            MyNodeTemplate::Select,
+        );
+        let condition = app.state.graph[select].get_input("condition").unwrap();
+        let if_true = app.state.graph[select].get_input("if true").unwrap();
+        let if_false = app.state.graph[select].get_input("if false").unwrap();
+
+        // The condition is true, which would otherwise count as a 1.
+        assert_eq!(app.inputs_matching(1.0), vec![if_true]);
+        app.replace_values(1.0, 0.5);
+        assert_eq!(
+            evaluate_scalar(&app.state.graph, &app.user_state, select),
+            0.5
+        );
+
+        // Replacing zeros doesn't turn the condition into true either.
+        set_scalar(&mut app.state.graph, select, "if true", 1.0);
+        app.state.graph[condition].value = MyValueType::Bool { value: false };
+        assert_eq!(app.inputs_matching(0.0), vec![if_false]);
+        app.replace_values(0.0, 2.0);
+        assert_eq!(
+            evaluate_scalar(&app.state.graph, &app.user_state, select),
+            2.0
+        );
+    }
+}
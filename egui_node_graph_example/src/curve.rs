@@ -0,0 +1,124 @@
+use eframe::egui::{self, Color32, Pos2, Sense, Shape, Stroke};
+
+/// A transfer function defined by a handful of control points in the unit
+/// square. The curve passes through every point, and is interpolated between
+/// them using a Catmull-Rom spline.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub struct Curve {
+    /// Control points, sorted by their `x` coordinate. The first and last
+    /// points are always pinned to `x = 0` and `x = 1` respectively.
+    pub points: Vec<Pos2>,
+}
+
+impl Default for Curve {
+    fn default() -> Self {
+        // The identity function, with a few points to grab onto.
+        Self {
+            points: (0..5)
+                .map(|i| {
+                    let t = i as f32 / 4.0;
+                    egui::pos2(t, t)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Curve {
+    /// Evaluates the curve at `x`. Values outside the `[0, 1]` range are
+    /// clamped to the endpoints of the curve.
+    pub fn sample(&self, x: f32) -> f32 {
+        let points = &self.points;
+        match points.len() {
+            0 => return x,
+            1 => return points[0].y,
+            _ => {}
+        }
+
+        let x = x.clamp(points[0].x, points[points.len() - 1].x);
+        let i = points
+            .windows(2)
+            .position(|w| x <= w[1].x)
+            .unwrap_or(points.len() - 2);
+
+        let p1 = points[i];
+        let p2 = points[i + 1];
+        // Endpoints are duplicated so the spline is defined on the first and
+        // last segments.
+        let p0 = points[i.saturating_sub(1)];
+        let p3 = points[(i + 2).min(points.len() - 1)];
+
+        let width = p2.x - p1.x;
+        if width <= f32::EPSILON {
+            return p1.y;
+        }
+        let t = (x - p1.x) / width;
+        let t2 = t * t;
+        let t3 = t2 * t;
+
+        0.5 * ((2.0 * p1.y)
+            + (-p0.y + p2.y) * t
+            + (2.0 * p0.y - 5.0 * p1.y + 4.0 * p2.y - p3.y) * t2
+            + (-p0.y + 3.0 * p1.y - 3.0 * p2.y + p3.y) * t3)
+    }
+}
+
+/// Draws an editable view of the curve. Control points can be dragged with the
+/// mouse. Returns true when the curve was modified.
+pub fn curve_editor(ui: &mut egui::Ui, id: egui::Id, curve: &mut Curve) -> bool {
+    // The slider width is scaled along with the graph zoom, so the editor
+    // keeps a consistent size relative to the rest of the node.
+    let width = ui.spacing().slider_width;
+    let (response, painter) = ui.allocate_painter(egui::vec2(width, width), Sense::hover());
+    let rect = response.rect;
+    let visuals = ui.visuals();
+
+    let to_screen = |p: Pos2| rect.lerp_inside(egui::vec2(p.x, 1.0 - p.y));
+    let point_radius = 4.0 * width / 100.0;
+
+    painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+
+    let samples = 32;
+    let line = (0..=samples)
+        .map(|i| {
+            let x = i as f32 / samples as f32;
+            to_screen(egui::pos2(x, curve.sample(x).clamp(0.0, 1.0)))
+        })
+        .collect();
+    painter.add(Shape::line(line, Stroke::new(1.5, visuals.text_color())));
+
+    let mut changed = false;
+    let last = curve.points.len().saturating_sub(1);
+    for i in 0..curve.points.len() {
+        let point_rect = egui::Rect::from_center_size(
+            to_screen(curve.points[i]),
+            egui::Vec2::splat(point_radius * 3.0),
+        );
+        let point_response = ui.interact(point_rect, id.with(i), Sense::drag());
+
+        let delta = point_response.drag_delta() / rect.size();
+        if delta != egui::Vec2::ZERO {
+            let point = &mut curve.points[i];
+            point.y = (point.y - delta.y).clamp(0.0, 1.0);
+            // Endpoints stay pinned horizontally. Inner points can't be dragged
+            // past their neighbours, to keep the points sorted.
+            if i != 0 && i != last {
+                let min_x = curve.points[i - 1].x;
+                let max_x = curve.points[i + 1].x;
+                let point = &mut curve.points[i];
+                point.x = (point.x + delta.x).clamp(min_x, max_x);
+            }
+            changed = true;
+        }
+
+        let color = if point_response.hovered() || point_response.dragged() {
+            Color32::WHITE
+        } else {
+            visuals.selection.bg_fill
+        };
+        painter.circle_filled(to_screen(curve.points[i]), point_radius, color);
+    }
+
+    changed
+}
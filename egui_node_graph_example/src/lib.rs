@@ -5,6 +5,8 @@
 mod app;
 pub use app::NodeGraphExample;
 
+mod curve;
+
 // ----------------------------------------------------------------------------
 // When compiling for web:
 